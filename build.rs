@@ -1,14 +1,89 @@
 const COMMANDS: &[&str] = &[
     "list_adapters",
     "set_adapter_powered",
+    "get_default_adapter",
+    "set_default_adapter",
+    "get_rfkill_state",
+    "rfkill_block",
+    "rfkill_unblock",
     "get_adapter_state",
     "start_scan",
     "stop_scan",
+    "register_advertisement_monitor",
+    "unregister_advertisement_monitor",
+    "list_advertisement_monitors",
     "list_devices",
     "list_paired_devices",
     "connect_device",
+    "connect_device_with_options",
+    "cancel_connect",
     "disconnect_device",
+    "set_auto_reconnect",
+    "list_auto_reconnect",
+    "pair_device",
+    "cancel_pairing",
+    "pairing_respond",
+    "set_device_trusted",
+    "setup_hid_device",
+    "connect_profile",
+    "disconnect_profile",
+    "network_connect",
+    "network_disconnect",
+    "network_server_register",
+    "network_server_unregister",
+    "register_profile",
+    "unregister_profile",
+    "list_profile_connections",
+    "profile_write",
+    "profile_close",
+    "spp_connect",
+    "spp_write",
+    "spp_pause",
+    "spp_resume",
+    "spp_close",
+    "list_spp_connections",
+    "send_file",
+    "cancel_transfer",
+    "register_obex_agent",
+    "unregister_obex_agent",
+    "accept_incoming_transfer",
+    "reject_incoming_transfer",
+    "get_obex_receive_config",
+    "set_obex_receive_config",
+    "ftp_connect",
+    "ftp_disconnect",
+    "ftp_list_folder",
+    "ftp_change_folder",
+    "ftp_create_folder",
+    "ftp_delete",
+    "ftp_get_file",
+    "ftp_put_file",
+    "pbap_list",
+    "pbap_pull_all",
+    "pbap_pull",
+    "map_connect",
+    "map_disconnect",
+    "map_list_folders",
+    "map_list_messages",
+    "map_get_message",
+    "map_set_message_read",
+    "map_push_message",
     "get_device_info",
+    "resolve_device_path",
+    "get_device_by_address",
+    "list_device_history",
+    "get_device_history",
+    "set_device_metadata",
+    "forget_device_history",
+    "get_device_proximity",
+    "list_device_proximity",
+    "get_presence_config",
+    "set_presence_config",
+    "get_event_throttle",
+    "set_event_throttle",
+    "subscribe_events",
+    "unsubscribe_events",
+    "list_event_subscriptions",
     "bluetooth_plugin_status",
 ];
 
@@ -1,4 +1,4 @@
-import { invoke } from '@tauri-apps/api/core'
+import { Channel, invoke } from '@tauri-apps/api/core'
 
 // ============================================================================
 // TYPES / INTERFACES
@@ -29,6 +29,7 @@ export interface AdapterInfo {
 }
 
 export interface DeviceInfo {
+  id: string; // Estable entre adaptadores (dirección normalizada)
   path: string;
   address: string; // MAC address
   name?: string;
@@ -46,13 +47,310 @@ export interface DeviceInfo {
   uuids: string[];
   adapter: string; // ObjectPath del adaptador al que pertenece
   servicesResolved: boolean;
+  hid?: HidInfo; // Solo para teclados, ratones, mandos...
+  history?: DeviceHistory; // Metadatos persistidos por el plugin
 }
 
+export interface DeviceHistory {
+  address: string;
+  name?: string; // Last known name or alias
+  first_seen: number; // Unix milliseconds
+  last_seen: number;
+  last_connected?: number;
+  last_rssi?: number;
+  connection_count: number;
+  nickname?: string;
+  tags: string[];
+  favorite: boolean;
+}
+
+export interface MonitorPattern {
+  startPosition: number; // Offset inside the AD data
+  adType: number; // AD type, e.g. 0xff (Manufacturer Specific Data)
+  content: number[];
+}
+
+export interface AdvertisementMonitorOptions {
+  patterns: MonitorPattern[]; // or_patterns: any match counts
+  rssiLowThreshold?: number; // Set both thresholds or none
+  rssiHighThreshold?: number;
+  rssiLowTimeout?: number; // Seconds below the low threshold before DeviceLost (default 5)
+  rssiHighTimeout?: number; // Seconds above the high threshold before DeviceFound (default 1)
+  rssiSamplingPeriod?: number; // Units of 100 ms
+}
+
+export interface AdvertisementMonitorInfo {
+  id: string;
+  adapter: string;
+  options: AdvertisementMonitorOptions;
+}
+
+export interface PresenceConfig {
+  nearRssi: number; // Smoothed dBm to become near (default -65)
+  hysteresis: number; // dB below nearRssi to fall back to far (default 8)
+  goneTimeoutMs: number; // No RSSI nor connection for this long: gone (default 30000)
+  smoothing: number; // EMA alpha in (0, 1] (default 0.25)
+}
+
+export interface ThrottleConfig {
+  defaultIntervalMs: number; // Minimum gap per property (default 500)
+  intervals: Record<string, number>; // Per property, e.g. { RSSI: 2000 }
+  immediate: string[]; // Never held back (Connected, Paired, Name, ...)
+  coalesceMs: number; // Batch a device's changes within this window (0 = off)
+  rssi: boolean; // Deliver RSSI changes (default false)
+  manufacturerData: boolean; // Deliver ManufacturerData changes (default false)
+  snapshot: boolean; // Add the merged DeviceInfo to each event (default false)
+}
+
+/**
+ * Payload of `device-property-changed`: only the properties that changed, by BlueZ name
+ */
+export interface DevicePropertiesChanged {
+  path: string;
+  address?: string;
+  changed: Record<string, unknown>;
+  invalidated: string[];
+  device?: DeviceInfo; // With ThrottleConfig.snapshot, from the plugin's cache
+}
+
+/**
+ * Payload of `adapter-property-changed`
+ */
+export interface AdapterPropertiesChanged {
+  path: string;
+  changed: Record<string, unknown>;
+  invalidated: string[];
+  adapter?: AdapterInfo;
+}
+
+export interface ProximityInfo {
+  address: string;
+  path: string;
+  state: 'near' | 'far' | 'gone';
+  rssi?: number; // Smoothed
+  rawRssi?: number; // Last sample
+  distance?: number; // Meters, rough estimate
+  connected: boolean;
+}
+
+export interface DeviceMetadataUpdate {
+  nickname?: string; // "" clears it
+  tags?: string[];
+  favorite?: boolean;
+}
+
+export interface HidInfo {
+  transport: 'hogp' | 'classic';
+  reconnect_mode?: 'none' | 'host' | 'device' | 'any';
+  virtual_cable?: boolean;
+}
+
+export interface ProfileOptions {
+  uuid: string; // Full UUID, short form ("1101") or friendly name ("spp")
+  name?: string;
+  role?: 'client' | 'server';
+  channel?: number; // RFCOMM channel
+  psm?: number; // L2CAP PSM
+  requireAuthentication?: boolean;
+  requireAuthorization?: boolean;
+  autoConnect?: boolean;
+  serviceRecord?: string; // SDP record XML
+}
+
+export interface ProfileConnection {
+  id: string;
+  uuid: string;
+  device: string; // ObjectPath del dispositivo
+  address: string;
+}
+
+/** Payload of `profile-data` events */
+export interface ProfileData {
+  id: string;
+  data: number[];
+}
+
+export interface ConnectOptions {
+  id?: string; // Used by cancelConnect, defaults to the device path
+  timeoutMs?: number; // Per attempt, 15000 by default
+  retries?: number; // Extra attempts after the first one, 0 by default
+  backoffMs?: number; // First delay between attempts (doubles each time), 1000 by default
+  waitServicesResolved?: boolean;
+}
+
+export interface AutoReconnectInfo {
+  address: string;
+  reconnecting: boolean; // A reconnect loop is running
+  userDisconnected: boolean; // Suspended by disconnectDevice until the next connection
+}
+
+export interface SppOptions {
+  mode?: 'raw' | 'line' | 'framed'; // framed = 2-byte big-endian length prefix
+  delimiter?: string; // Line mode only, "\n" by default
+  reconnect?: boolean;
+  maxReconnectAttempts?: number;
+}
+
+export interface SppConnectionInfo {
+  id: string;
+  address: string;
+  device: string;
+  mode: 'raw' | 'line' | 'framed';
+  connected: boolean;
+  paused: boolean;
+}
+
+/** Payload of `spp-data` events: one chunk (raw), line or frame */
+export interface SppData {
+  id: string;
+  data: number[];
+  text?: string; // Line mode only
+}
+
+export interface RfkillState {
+  index: number;
+  name: string; // hci0, or a platform switch such as tpacpi_bluetooth_sw
+  adapter?: string; // Adapter object path; undefined when the switch affects every adapter
+  softBlocked: boolean;
+  hardBlocked: boolean;
+}
+
+/** Payload of `pairing-request` (needs pairingRespond) and `pairing-display` events. */
+export interface PairingRequest {
+  device: string;
+  kind: 'pin-code' | 'passkey' | 'confirmation' | 'authorization' | 'service-authorization';
+  passkey?: string;
+  entered?: number; // Dígitos tecleados en el teclado (pairing-display)
+  uuid?: string;
+}
+
+export interface ObexTransfer {
+  path: string; // ObjectPath del Transfer1 en el bus de sesión
+  session?: string;
+  name?: string;
+  filename?: string;
+  size?: number;
+  transferred: number;
+  status: 'queued' | 'active' | 'suspended' | 'complete' | 'error';
+}
+
+export interface IncomingTransfer {
+  path: string; // ObjectPath del Transfer1 entrante
+  deviceAddress?: string;
+  name?: string;
+  size?: number;
+}
+
+export interface ObexReceiveConfig {
+  downloadDir: string;
+  maxFileSize?: number; // bytes; undefined = sin límite
+}
+
+export interface FtpEntry {
+  name: string;
+  type: 'folder' | 'file';
+  size?: number;
+  modified?: string; // ISO 8601 (UTC)
+}
+
+export interface PhonebookEntry {
+  handle: string; // Ej: "0.vcf"
+  name: string;
+}
+
+export interface VCardField {
+  value: string;
+  types: string[]; // Ej: ["cell", "voice"]
+}
+
+export interface VCardContact {
+  version?: string;
+  uid?: string;
+  formattedName?: string;
+  familyName?: string;
+  givenName?: string;
+  phones: VCardField[];
+  emails: VCardField[];
+  addresses: VCardField[];
+  organization?: string;
+  title?: string;
+  birthday?: string;
+  note?: string;
+  callType?: 'missed' | 'received' | 'dialed';
+  callDatetime?: string; // X-IRMC-CALL-DATETIME
+}
+
+/** PBAP repository */
+export type PhonebookLocation = 'internal' | 'sim';
+
+/** pb = contacts, ich/och/mch = incoming/outgoing/missed calls, cch = combined history */
+export type Phonebook = 'pb' | 'ich' | 'och' | 'mch' | 'cch' | 'spd' | 'fav';
+
+export interface MapMessage {
+  path: string; // ObjectPath del Message1 en la sesión MAP
+  folder?: string;
+  subject?: string;
+  timestamp?: string;
+  sender?: string;
+  senderAddress?: string;
+  recipient?: string;
+  recipientAddress?: string;
+  messageType?: string; // sms-gsm, sms-cdma, email, mms
+  size?: number;
+  status?: string;
+  read: boolean;
+  sent: boolean;
+}
+
+export interface MapMessageFilter {
+  offset?: number;
+  maxCount?: number;
+  unreadOnly?: boolean;
+  periodBegin?: string; // "YYYYMMDDTHHMMSS"
+  periodEnd?: string;
+  sender?: string;
+}
+
+export interface BMessage {
+  version?: string;
+  status?: string; // READ / UNREAD
+  messageType?: string; // SMS_GSM, SMS_CDMA, EMAIL, MMS
+  folder?: string;
+  originator?: VCardContact;
+  recipients: VCardContact[];
+  charset?: string;
+  body: string;
+}
+
+/** PAN role: nap = Network Access Point, panu = PAN User, gn = Group ad-hoc Network */
+export type NetworkRole = 'nap' | 'panu' | 'gn';
+
 export interface BluetoothChange {
   changeType: string;
   data: any;
 }
 
+export interface ScanEvent {
+  seq: number; // 0 for initial, then +1 per message
+  kind: 'initial' | 'found' | 'updated' | 'lost' | 'stopped';
+  path?: string;
+  device?: DeviceInfo;
+  devices: DeviceInfo[]; // Cached devices, only in initial
+}
+
+export interface SubscriptionFilter {
+  events?: string[]; // BluetoothChangeType values; empty = all
+  adapters?: string[]; // Adapter paths; events not tied to one are dropped
+  devices?: string[]; // Addresses or device paths; events not tied to one are dropped
+  pairedOnly?: boolean; // Drop events about devices that aren't paired
+}
+
+export interface SubscriptionInfo {
+  id: number;
+  window: string;
+  filter: SubscriptionFilter;
+}
+
 // ============================================================================
 // API FUNCTIONS
 // ============================================================================
@@ -65,7 +363,7 @@ export async function listAdapters(): Promise<AdapterInfo[]> {
 }
 
 /**
- * Set adapter power state (on/off)
+ * Set adapter power state (on/off). Powering on clears a soft rfkill block first.
  */
 export async function setAdapterPowered(adapterPath: string, powered: boolean): Promise<void> {
   return await invoke<void>('plugin:bluetooth-manager|set_adapter_powered', {
@@ -74,6 +372,53 @@ export async function setAdapterPowered(adapterPath: string, powered: boolean):
   });
 }
 
+/**
+ * Pass as `adapterPath` to any command to target the default adapter
+ */
+export const DEFAULT_ADAPTER = 'default';
+
+/**
+ * The default adapter: the user's choice when plugged in, otherwise the first powered one
+ */
+export async function getDefaultAdapter(): Promise<AdapterInfo | null> {
+  return await invoke<AdapterInfo | null>('plugin:bluetooth-manager|get_default_adapter');
+}
+
+/**
+ * Remember an adapter (object path or address) as the default. It is stored by address, so it
+ * survives hciN renumbering. Pass nothing to go back to the first powered adapter.
+ */
+export async function setDefaultAdapter(adapter?: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|set_default_adapter', {
+    adapter,
+  });
+}
+
+/**
+ * Bluetooth rfkill switches with their soft/hard block state
+ */
+export async function getRfkillState(): Promise<RfkillState[]> {
+  return await invoke<RfkillState[]>('plugin:bluetooth-manager|get_rfkill_state');
+}
+
+/**
+ * Soft-block one adapter, or every Bluetooth radio when no path is given (airplane mode)
+ */
+export async function rfkillBlock(adapterPath?: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|rfkill_block', {
+    adapterPath,
+  });
+}
+
+/**
+ * Clear the soft block of one adapter, or of every Bluetooth radio when no path is given
+ */
+export async function rfkillUnblock(adapterPath?: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|rfkill_unblock', {
+    adapterPath,
+  });
+}
+
 /**
  * Get current state of a specific adapter
  */
@@ -84,11 +429,25 @@ export async function getAdapterState(adapterPath: string): Promise<AdapterInfo>
 }
 
 /**
- * Start device discovery (scan) on an adapter
+ * Start device discovery (scan) on an adapter. With `onEvent`, this scan's results are
+ * streamed to it until `stopScan`: an `initial` message with the cached devices, then
+ * `found`/`updated`/`lost` in order. Devices are lost after `staleTimeoutMs` (default
+ * 30000) without any update.
  */
-export async function startScan(adapterPath: string): Promise<void> {
+export async function startScan(
+  adapterPath: string,
+  onEvent?: (event: ScanEvent) => void,
+  staleTimeoutMs?: number,
+): Promise<void> {
+  let channel: Channel<ScanEvent> | undefined;
+  if (onEvent) {
+    channel = new Channel<ScanEvent>();
+    channel.onmessage = onEvent;
+  }
   return await invoke<void>('plugin:bluetooth-manager|start_scan', {
     adapterPath,
+    onEvent: channel,
+    staleTimeoutMs,
   });
 }
 
@@ -101,6 +460,30 @@ export async function stopScan(adapterPath: string): Promise<void> {
   });
 }
 
+/**
+ * Passive background scanning: get advertisement-monitor-device-found/lost events for
+ * devices whose advertisements match a pattern, without keeping discovery on
+ */
+export async function registerAdvertisementMonitor(
+  adapterPath: string,
+  options: AdvertisementMonitorOptions
+): Promise<string> {
+  return await invoke<string>('plugin:bluetooth-manager|register_advertisement_monitor', {
+    adapterPath,
+    options,
+  });
+}
+
+export async function unregisterAdvertisementMonitor(id: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|unregister_advertisement_monitor', { id });
+}
+
+export async function listAdvertisementMonitors(): Promise<AdvertisementMonitorInfo[]> {
+  return await invoke<AdvertisementMonitorInfo[]>(
+    'plugin:bluetooth-manager|list_advertisement_monitors'
+  );
+}
+
 /**
  * List all devices associated with an adapter
  */
@@ -119,6 +502,134 @@ export async function getDeviceInfo(devicePath: string): Promise<DeviceInfo> {
   });
 }
 
+/**
+ * Resolve a device address to its current object path.
+ * `adapterHint` (path, address or "default") picks the adapter when several know the device.
+ */
+export async function resolveDevicePath(address: string, adapterHint?: string): Promise<string> {
+  return await invoke<string>('plugin:bluetooth-manager|resolve_device_path', {
+    address,
+    adapterHint,
+  });
+}
+
+/**
+ * Get device info by address, wherever the device currently lives
+ */
+export async function getDeviceByAddress(address: string, adapterHint?: string): Promise<DeviceInfo> {
+  return await invoke<DeviceInfo>('plugin:bluetooth-manager|get_device_by_address', {
+    address,
+    adapterHint,
+  });
+}
+
+/**
+ * Devices remembered by the plugin, most recently seen first
+ */
+export async function listDeviceHistory(
+  favoritesOnly?: boolean,
+  tag?: string
+): Promise<DeviceHistory[]> {
+  return await invoke<DeviceHistory[]>('plugin:bluetooth-manager|list_device_history', {
+    favoritesOnly,
+    tag,
+  });
+}
+
+/**
+ * What the plugin remembers about one device, if anything
+ */
+export async function getDeviceHistory(address: string): Promise<DeviceHistory | null> {
+  return await invoke<DeviceHistory | null>('plugin:bluetooth-manager|get_device_history', {
+    address,
+  });
+}
+
+/**
+ * Set the nickname, tags or favorite flag of a device
+ */
+export async function setDeviceMetadata(
+  address: string,
+  metadata: DeviceMetadataUpdate
+): Promise<DeviceHistory> {
+  return await invoke<DeviceHistory>('plugin:bluetooth-manager|set_device_metadata', {
+    address,
+    metadata,
+  });
+}
+
+/**
+ * Presence state of a device (near/far/gone), smoothed RSSI and estimated distance
+ */
+export async function getDeviceProximity(address: string): Promise<ProximityInfo | null> {
+  return await invoke<ProximityInfo | null>('plugin:bluetooth-manager|get_device_proximity', {
+    address,
+  });
+}
+
+/**
+ * Presence state of every device seen since startup
+ */
+export async function listDeviceProximity(): Promise<ProximityInfo[]> {
+  return await invoke<ProximityInfo[]>('plugin:bluetooth-manager|list_device_proximity');
+}
+
+export async function getPresenceConfig(): Promise<PresenceConfig> {
+  return await invoke<PresenceConfig>('plugin:bluetooth-manager|get_presence_config');
+}
+
+/**
+ * Tune the near threshold, hysteresis, gone timeout and RSSI smoothing
+ */
+export async function setPresenceConfig(config: Partial<PresenceConfig>): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|set_presence_config', { config });
+}
+
+export async function getEventThrottle(): Promise<ThrottleConfig> {
+  return await invoke<ThrottleConfig>('plugin:bluetooth-manager|get_event_throttle');
+}
+
+/**
+ * Rate limits of `device-property-changed`; applies to changes from now on
+ */
+export async function setEventThrottle(config: ThrottleConfig): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|set_event_throttle', { config });
+}
+
+/**
+ * Receive only the `bluetooth-change` events matching `filter` in this window.
+ * The subscription ends with `unsubscribeEvents` or when the window closes.
+ */
+export async function subscribeEvents(
+  filter: SubscriptionFilter,
+  onEvent: (change: BluetoothChange) => void,
+): Promise<number> {
+  const channel = new Channel<BluetoothChange>();
+  channel.onmessage = onEvent;
+  return await invoke<number>('plugin:bluetooth-manager|subscribe_events', {
+    filter,
+    onEvent: channel,
+  });
+}
+
+/**
+ * End a subscription made by this window.
+ */
+export async function unsubscribeEvents(id: number): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|unsubscribe_events', { id });
+}
+
+export async function listEventSubscriptions(): Promise<SubscriptionInfo[]> {
+  return await invoke<SubscriptionInfo[]>('plugin:bluetooth-manager|list_event_subscriptions');
+}
+
+/**
+ * Drop everything the plugin remembers about a device
+ */
+export async function forgetDeviceHistory(address: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|forget_device_history', { address });
+}
+
 /**
  * List only paired devices for an adapter
  */
@@ -137,6 +648,27 @@ export async function connectDevice(devicePath: string): Promise<void> {
   });
 }
 
+/**
+ * Connect with a per-attempt timeout and retries. Resolves with the device once it is
+ * really connected (and its services resolved, if asked); `connect-progress` events report attempts.
+ */
+export async function connectDeviceWithOptions(
+  devicePath: string,
+  options?: ConnectOptions
+): Promise<DeviceInfo> {
+  return await invoke<DeviceInfo>('plugin:bluetooth-manager|connect_device_with_options', {
+    devicePath,
+    options,
+  });
+}
+
+/**
+ * Cancel a pending connectDeviceWithOptions call by its id
+ */
+export async function cancelConnect(id: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|cancel_connect', { id });
+}
+
 /**
  * Disconnect from a Bluetooth device
  */
@@ -146,6 +678,517 @@ export async function disconnectDevice(devicePath: string): Promise<void> {
   });
 }
 
+/**
+ * Reconnect a device automatically when it drops, comes back in range or its adapter
+ * powers on. `disconnectDevice` suspends it until the device connects again.
+ */
+export async function setAutoReconnect(address: string, enabled: boolean): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|set_auto_reconnect', {
+    address,
+    enabled,
+  });
+}
+
+/**
+ * Devices marked for auto-reconnect and what the reconnect manager is doing with them
+ */
+export async function listAutoReconnect(): Promise<AutoReconnectInfo[]> {
+  return await invoke<AutoReconnectInfo[]>('plugin:bluetooth-manager|list_auto_reconnect');
+}
+
+/**
+ * Pair with a device. Passkeys and confirmations arrive as `pairing-display` / `pairing-request` events.
+ */
+export async function pairDevice(devicePath: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|pair_device', {
+    devicePath,
+  });
+}
+
+/**
+ * Abort an ongoing pairing
+ */
+export async function cancelPairing(devicePath: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|cancel_pairing', {
+    devicePath,
+  });
+}
+
+/**
+ * Answer a `pairing-request` event. `value` carries the PIN code or passkey when asked for one.
+ */
+export async function pairingRespond(devicePath: string, accept: boolean, value?: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|pairing_respond', {
+    devicePath,
+    accept,
+    value,
+  });
+}
+
+/**
+ * Mark a device as trusted so it can reconnect without asking
+ */
+export async function setDeviceTrusted(devicePath: string, trusted: boolean): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|set_device_trusted', {
+    devicePath,
+    trusted,
+  });
+}
+
+/**
+ * Pair, trust and connect a keyboard, mouse or gamepad in one go.
+ * Progress is reported through `hid-setup-progress` events.
+ */
+export async function setupHidDevice(devicePath: string): Promise<DeviceInfo> {
+  return await invoke<DeviceInfo>('plugin:bluetooth-manager|setup_hid_device', {
+    devicePath,
+  });
+}
+
+/**
+ * Connect a single profile on a device.
+ * Accepts a raw UUID or a friendly name such as "a2dp-sink", "hfp-hf", "hid" or "pan-nap".
+ */
+export async function connectProfile(devicePath: string, uuid: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|connect_profile', {
+    devicePath,
+    uuid,
+  });
+}
+
+/**
+ * Disconnect a single profile on a device (raw UUID or friendly name)
+ */
+export async function disconnectProfile(devicePath: string, uuid: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|disconnect_profile', {
+    devicePath,
+    uuid,
+  });
+}
+
+/**
+ * Tether through a device's PAN service. Returns the created interface name (e.g. "bnep0").
+ */
+export async function networkConnect(devicePath: string, role: NetworkRole = 'nap'): Promise<string> {
+  return await invoke<string>('plugin:bluetooth-manager|network_connect', {
+    devicePath,
+    role,
+  });
+}
+
+/**
+ * Disconnect the PAN connection of a device
+ */
+export async function networkDisconnect(devicePath: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|network_disconnect', {
+    devicePath,
+  });
+}
+
+/**
+ * Share this machine's connection through a bridge (NetworkServer1.Register)
+ */
+export async function networkServerRegister(adapterPath: string, role: NetworkRole, bridge: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|network_server_register', {
+    adapterPath,
+    role,
+    bridge,
+  });
+}
+
+/**
+ * Stop sharing this machine's connection for a role
+ */
+export async function networkServerUnregister(adapterPath: string, role: NetworkRole): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|network_server_unregister', {
+    adapterPath,
+    role,
+  });
+}
+
+/**
+ * Register a custom RFCOMM/L2CAP profile with BlueZ. Connections are reported as
+ * `profile-connected`, incoming bytes as `profile-data`. Returns the profile object path.
+ * Outgoing connections are opened with `connectProfile(devicePath, uuid)`.
+ */
+export async function registerProfile(options: ProfileOptions): Promise<string> {
+  return await invoke<string>('plugin:bluetooth-manager|register_profile', {
+    options,
+  });
+}
+
+/**
+ * Unregister a custom profile and close its connections
+ */
+export async function unregisterProfile(uuid: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|unregister_profile', {
+    uuid,
+  });
+}
+
+/**
+ * List open custom profile connections
+ */
+export async function listProfileConnections(): Promise<ProfileConnection[]> {
+  return await invoke<ProfileConnection[]>('plugin:bluetooth-manager|list_profile_connections');
+}
+
+/**
+ * Write bytes to a profile connection. Resolves once the socket has accepted all of them.
+ */
+export async function profileWrite(id: string, data: Uint8Array | number[]): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|profile_write', {
+    id,
+    data: Array.from(data),
+  });
+}
+
+/**
+ * Close a profile connection
+ */
+export async function profileClose(id: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|profile_close', {
+    id,
+  });
+}
+
+/**
+ * Open a Serial Port Profile connection to a device. Incoming messages arrive as `spp-data`.
+ * Returns the connection id.
+ */
+export async function sppConnect(deviceAddress: string, options?: SppOptions): Promise<string> {
+  return await invoke<string>('plugin:bluetooth-manager|spp_connect', {
+    deviceAddress,
+    options,
+  });
+}
+
+/**
+ * Send one message: raw bytes, a line (the delimiter is appended) or a frame, depending on the mode.
+ * Strings are sent as UTF-8. Waits while the write queue is full.
+ */
+export async function sppWrite(id: string, data: string | Uint8Array | number[]): Promise<void> {
+  const bytes = typeof data === 'string' ? new TextEncoder().encode(data) : data;
+  return await invoke<void>('plugin:bluetooth-manager|spp_write', {
+    id,
+    data: Array.from(bytes),
+  });
+}
+
+/**
+ * Stop reading from an SPP connection; the device is throttled by RFCOMM flow control
+ */
+export async function sppPause(id: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|spp_pause', {
+    id,
+  });
+}
+
+/**
+ * Resume reading from a paused SPP connection
+ */
+export async function sppResume(id: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|spp_resume', {
+    id,
+  });
+}
+
+/**
+ * Close an SPP connection (no reconnection is attempted)
+ */
+export async function sppClose(id: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|spp_close', {
+    id,
+  });
+}
+
+/**
+ * List open SPP connections
+ */
+export async function listSppConnections(): Promise<SppConnectionInfo[]> {
+  return await invoke<SppConnectionInfo[]>('plugin:bluetooth-manager|list_spp_connections');
+}
+
+/**
+ * Send a file to a device over OBEX Object Push (requires obexd on the session bus).
+ * Progress is reported through `obex-transfer-*` events.
+ */
+export async function sendFile(deviceAddress: string, filePath: string): Promise<ObexTransfer> {
+  return await invoke<ObexTransfer>('plugin:bluetooth-manager|send_file', {
+    deviceAddress,
+    filePath,
+  });
+}
+
+/**
+ * Cancel an active OBEX transfer
+ */
+export async function cancelTransfer(transferPath: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|cancel_transfer', {
+    transferPath,
+  });
+}
+
+/**
+ * Register the OBEX agent so incoming pushes are announced as `obex-incoming-request` events
+ */
+export async function registerObexAgent(): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|register_obex_agent');
+}
+
+/**
+ * Unregister the OBEX agent (incoming pushes are no longer accepted)
+ */
+export async function unregisterObexAgent(): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|unregister_obex_agent');
+}
+
+/**
+ * Accept a pending incoming transfer, optionally into a specific directory
+ */
+export async function acceptIncomingTransfer(transferPath: string, targetDir?: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|accept_incoming_transfer', {
+    transferPath,
+    targetDir,
+  });
+}
+
+/**
+ * Reject a pending incoming transfer
+ */
+export async function rejectIncomingTransfer(transferPath: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|reject_incoming_transfer', {
+    transferPath,
+  });
+}
+
+/**
+ * Get the default download directory and size limit for incoming transfers
+ */
+export async function getObexReceiveConfig(): Promise<ObexReceiveConfig> {
+  return await invoke<ObexReceiveConfig>('plugin:bluetooth-manager|get_obex_receive_config');
+}
+
+/**
+ * Set the default download directory and size limit for incoming transfers
+ */
+export async function setObexReceiveConfig(config: ObexReceiveConfig): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|set_obex_receive_config', {
+    config,
+  });
+}
+
+/**
+ * Open an OBEX FTP session with a device. Returns the session id used by the other ftp* calls.
+ * Sessions are closed automatically after 5 minutes without use.
+ */
+export async function ftpConnect(deviceAddress: string): Promise<string> {
+  return await invoke<string>('plugin:bluetooth-manager|ftp_connect', {
+    deviceAddress,
+  });
+}
+
+/**
+ * Close an OBEX FTP session
+ */
+export async function ftpDisconnect(session: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|ftp_disconnect', {
+    session,
+  });
+}
+
+/**
+ * List the current remote folder
+ */
+export async function ftpListFolder(session: string): Promise<FtpEntry[]> {
+  return await invoke<FtpEntry[]>('plugin:bluetooth-manager|ftp_list_folder', {
+    session,
+  });
+}
+
+/**
+ * Change the current remote folder ("" for root, ".." for parent)
+ */
+export async function ftpChangeFolder(session: string, folder: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|ftp_change_folder', {
+    session,
+    folder,
+  });
+}
+
+/**
+ * Create a folder inside the current remote folder
+ */
+export async function ftpCreateFolder(session: string, folder: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|ftp_create_folder', {
+    session,
+    folder,
+  });
+}
+
+/**
+ * Delete a file or folder from the current remote folder
+ */
+export async function ftpDelete(session: string, name: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|ftp_delete', {
+    session,
+    name,
+  });
+}
+
+/**
+ * Download a remote file into a local path (progress via `obex-transfer-*` events)
+ */
+export async function ftpGetFile(session: string, remoteName: string, localPath: string): Promise<ObexTransfer> {
+  return await invoke<ObexTransfer>('plugin:bluetooth-manager|ftp_get_file', {
+    session,
+    remoteName,
+    localPath,
+  });
+}
+
+/**
+ * Upload a local file into the current remote folder (progress via `obex-transfer-*` events)
+ */
+export async function ftpPutFile(session: string, localPath: string, remoteName: string): Promise<ObexTransfer> {
+  return await invoke<ObexTransfer>('plugin:bluetooth-manager|ftp_put_file', {
+    session,
+    localPath,
+    remoteName,
+  });
+}
+
+/**
+ * List the entries (handle + name) of a phonebook over PBAP
+ */
+export async function pbapList(
+  deviceAddress: string,
+  location: PhonebookLocation,
+  phonebook: Phonebook,
+  offset?: number,
+  maxCount?: number,
+): Promise<PhonebookEntry[]> {
+  return await invoke<PhonebookEntry[]>('plugin:bluetooth-manager|pbap_list', {
+    deviceAddress,
+    location,
+    phonebook,
+    offset,
+    maxCount,
+  });
+}
+
+/**
+ * Pull a whole phonebook or call history over PBAP as parsed contacts
+ */
+export async function pbapPullAll(
+  deviceAddress: string,
+  location: PhonebookLocation,
+  phonebook: Phonebook,
+  offset?: number,
+  maxCount?: number,
+): Promise<VCardContact[]> {
+  return await invoke<VCardContact[]>('plugin:bluetooth-manager|pbap_pull_all', {
+    deviceAddress,
+    location,
+    phonebook,
+    offset,
+    maxCount,
+  });
+}
+
+/**
+ * Pull a single vCard by handle over PBAP
+ */
+export async function pbapPull(
+  deviceAddress: string,
+  location: PhonebookLocation,
+  phonebook: Phonebook,
+  handle: string,
+): Promise<VCardContact> {
+  return await invoke<VCardContact>('plugin:bluetooth-manager|pbap_pull', {
+    deviceAddress,
+    location,
+    phonebook,
+    handle,
+  });
+}
+
+/**
+ * Open a MAP session with a phone. New incoming messages are emitted as `map-message-received`
+ * until `mapDisconnect` is called.
+ */
+export async function mapConnect(deviceAddress: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|map_connect', {
+    deviceAddress,
+  });
+}
+
+/**
+ * Close the MAP session with a phone
+ */
+export async function mapDisconnect(deviceAddress: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|map_disconnect', {
+    deviceAddress,
+  });
+}
+
+/**
+ * List message folders (defaults to "/telecom/msg")
+ */
+export async function mapListFolders(deviceAddress: string, folder?: string): Promise<string[]> {
+  return await invoke<string[]>('plugin:bluetooth-manager|map_list_folders', {
+    deviceAddress,
+    folder,
+  });
+}
+
+/**
+ * List messages in a folder ("inbox", "sent", ... or an absolute path)
+ */
+export async function mapListMessages(
+  deviceAddress: string,
+  folder: string,
+  filter?: MapMessageFilter,
+): Promise<MapMessage[]> {
+  return await invoke<MapMessage[]>('plugin:bluetooth-manager|map_list_messages', {
+    deviceAddress,
+    folder,
+    filter,
+  });
+}
+
+/**
+ * Fetch and parse the full content of a message
+ */
+export async function mapGetMessage(deviceAddress: string, messagePath: string): Promise<BMessage> {
+  return await invoke<BMessage>('plugin:bluetooth-manager|map_get_message', {
+    deviceAddress,
+    messagePath,
+  });
+}
+
+/**
+ * Mark a message as read or unread
+ */
+export async function mapSetMessageRead(deviceAddress: string, messagePath: string, read: boolean): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|map_set_message_read', {
+    deviceAddress,
+    messagePath,
+    read,
+  });
+}
+
+/**
+ * Send an SMS through the phone
+ */
+export async function mapPushMessage(deviceAddress: string, recipient: string, text: string): Promise<void> {
+  return await invoke<void>('plugin:bluetooth-manager|map_push_message', {
+    deviceAddress,
+    recipient,
+    text,
+  });
+}
+
 /**
  * Check if the bluetooth plugin was initialized correctly
  */
@@ -298,11 +1341,40 @@ export const BluetoothChangeType = {
   ADAPTER_ADDED: 'adapter-added',
   ADAPTER_REMOVED: 'adapter-removed',
   ADAPTER_PROPERTY_CHANGED: 'adapter-property-changed',
+  DEFAULT_ADAPTER_CHANGED: 'default-adapter-changed',
   DEVICE_ADDED: 'device-added',
   DEVICE_REMOVED: 'device-removed',
   DEVICE_CONNECTED: 'device-connected',
   DEVICE_DISCONNECTED: 'device-disconnected',
   DEVICE_PROPERTY_CHANGED: 'device-property-changed',
+  DEVICE_NETWORK_CHANGED: 'device-network-changed',
+  DEVICE_PROXIMITY_CHANGED: 'device-proximity-changed',
+  ADVERTISEMENT_MONITOR_ACTIVATED: 'advertisement-monitor-activated',
+  ADVERTISEMENT_MONITOR_RELEASED: 'advertisement-monitor-released',
+  ADVERTISEMENT_MONITOR_DEVICE_FOUND: 'advertisement-monitor-device-found',
+  ADVERTISEMENT_MONITOR_DEVICE_LOST: 'advertisement-monitor-device-lost',
+  OBEX_TRANSFER_PROGRESS: 'obex-transfer-progress',
+  OBEX_TRANSFER_COMPLETE: 'obex-transfer-complete',
+  OBEX_TRANSFER_ERROR: 'obex-transfer-error',
+  OBEX_INCOMING_REQUEST: 'obex-incoming-request',
+  OBEX_INCOMING_REJECTED: 'obex-incoming-rejected',
+  OBEX_SESSION_CLOSED: 'obex-session-closed',
+  MAP_MESSAGE_RECEIVED: 'map-message-received',
+  PAIRING_REQUEST: 'pairing-request',
+  PAIRING_DISPLAY: 'pairing-display',
+  PAIRING_CANCELED: 'pairing-canceled',
+  HID_SETUP_PROGRESS: 'hid-setup-progress',
+  CONNECT_PROGRESS: 'connect-progress',
+  AUTO_RECONNECT_STATUS: 'auto-reconnect-status',
+  PROFILE_CONNECTED: 'profile-connected',
+  PROFILE_DATA: 'profile-data',
+  PROFILE_DISCONNECTED: 'profile-disconnected',
+  PROFILE_RELEASED: 'profile-released',
+  RFKILL_CHANGED: 'rfkill-changed',
+  SPP_CONNECTED: 'spp-connected',
+  SPP_DATA: 'spp-data',
+  SPP_DISCONNECTED: 'spp-disconnected',
+  SPP_RECONNECTING: 'spp-reconnecting',
   ERROR: 'error',
   DBUS_ERROR: 'dbus-error',
 } as const;
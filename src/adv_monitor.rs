@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime};
+use tracing::{error, info};
+use zbus::{
+    fdo::ObjectManager,
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value as ZbusValue},
+    Connection, Proxy,
+};
+
+use crate::desktop::{fetch_device_info, object_remover, BluetoothManager, ObjectRemover};
+use crate::device_history::Sighting;
+use crate::device_lookup::address_from_path;
+use crate::events::{emit_change, BluetoothEvent};
+use crate::models::{AdvertisementMonitorInfo, AdvertisementMonitorOptions};
+use crate::presence::Observation;
+use crate::{Error, Result};
+
+pub const MONITOR_BASE_PATH: &str = "/org/vasak/bluetooth/monitor";
+
+const MONITOR_TYPE: &str = "or_patterns";
+/// Maximum advertising data length a pattern can match in.
+const AD_MAX_LEN: usize = 31;
+// BlueZ "not set" values (src/adv_monitor.c)
+const UNSET_RSSI: i16 = 127;
+const UNSET_TIMEOUT: u16 = 0;
+const UNSET_SAMPLING_PERIOD: u16 = 256;
+const DEFAULT_LOW_TIMEOUT: u16 = 5;
+const DEFAULT_HIGH_TIMEOUT: u16 = 1;
+
+fn emit<R: Runtime>(app: &AppHandle<R>, change_type: &str, data: serde_json::Value) {
+    emit_change(app, BluetoothEvent::other(change_type, data));
+}
+
+/// One `org.bluez.AdvertisementMonitor1` object. BlueZ reads its properties once, when
+/// the object shows up under the registered root.
+struct Monitor<R: Runtime> {
+    app: AppHandle<R>,
+    id: String,
+    options: AdvertisementMonitorOptions,
+}
+
+#[zbus::interface(name = "org.bluez.AdvertisementMonitor1")]
+impl<R: Runtime> Monitor<R> {
+    async fn release(&self) {
+        info!("Advertisement monitor {} released", self.id);
+        emit(&self.app, "advertisement-monitor-released", serde_json::json!({ "id": self.id }));
+    }
+
+    async fn activate(&self) {
+        info!("Advertisement monitor {} active", self.id);
+        emit(&self.app, "advertisement-monitor-activated", serde_json::json!({ "id": self.id }));
+    }
+
+    async fn device_found(&self, device: OwnedObjectPath) {
+        let mut data = serde_json::json!({
+            "id": self.id,
+            "device": device.as_str(),
+            "address": address_from_path(device.as_str()),
+        });
+        let state = self.app.state::<BluetoothManager>();
+        if let Ok(mut info) = fetch_device_info(&state.conn, device.to_string()).await {
+            state.presence.observe(&self.app, device.as_str(), Observation {
+                rssi: info.rssi,
+                tx_power: info.tx_power,
+                connected: Some(info.connected),
+            });
+            state.device_history.observe(&info.id, Sighting::from_device(&info));
+            state.device_history.annotate(&mut info);
+            data["info"] = serde_json::to_value(info).unwrap_or_default();
+        }
+        emit(&self.app, "advertisement-monitor-device-found", data);
+    }
+
+    async fn device_lost(&self, device: OwnedObjectPath) {
+        // Below the low RSSI threshold for the low timeout: out of range
+        self.app.state::<BluetoothManager>().presence.remove(&self.app, device.as_str());
+        emit(&self.app, "advertisement-monitor-device-lost", serde_json::json!({
+            "id": self.id,
+            "device": device.as_str(),
+            "address": address_from_path(device.as_str()),
+        }));
+    }
+
+    #[zbus(property, name = "Type")]
+    fn monitor_type(&self) -> String {
+        MONITOR_TYPE.to_string()
+    }
+
+    #[zbus(property, name = "RSSILowThreshold")]
+    fn rssi_low_threshold(&self) -> i16 {
+        self.options.rssi_low_threshold.unwrap_or(UNSET_RSSI)
+    }
+
+    #[zbus(property, name = "RSSIHighThreshold")]
+    fn rssi_high_threshold(&self) -> i16 {
+        self.options.rssi_high_threshold.unwrap_or(UNSET_RSSI)
+    }
+
+    #[zbus(property, name = "RSSILowTimeout")]
+    fn rssi_low_timeout(&self) -> u16 {
+        match self.options.rssi_low_threshold {
+            Some(_) => self.options.rssi_low_timeout.unwrap_or(DEFAULT_LOW_TIMEOUT),
+            None => UNSET_TIMEOUT,
+        }
+    }
+
+    #[zbus(property, name = "RSSIHighTimeout")]
+    fn rssi_high_timeout(&self) -> u16 {
+        match self.options.rssi_high_threshold {
+            Some(_) => self.options.rssi_high_timeout.unwrap_or(DEFAULT_HIGH_TIMEOUT),
+            None => UNSET_TIMEOUT,
+        }
+    }
+
+    #[zbus(property, name = "RSSISamplingPeriod")]
+    fn rssi_sampling_period(&self) -> u16 {
+        self.options.rssi_sampling_period.unwrap_or(UNSET_SAMPLING_PERIOD)
+    }
+
+    #[zbus(property)]
+    fn patterns(&self) -> Vec<(u8, u8, Vec<u8>)> {
+        self.options
+            .patterns
+            .iter()
+            .map(|p| (p.start_position, p.ad_type, p.content.clone()))
+            .collect()
+    }
+}
+
+struct MonitorEntry {
+    info: AdvertisementMonitorInfo,
+    path: String,
+    remove: ObjectRemover,
+}
+
+/// Passive advertisement monitors, grouped under one ObjectManager root per adapter
+/// (`/org/vasak/bluetooth/monitor/hciN`) registered with `RegisterMonitor`.
+#[derive(Default)]
+pub struct AdvertisementMonitors {
+    monitors: Mutex<HashMap<String, MonitorEntry>>,
+    // Adapter path -> root path. Held across the awaits of (un)registration so concurrent
+    // calls agree on whether a root is registered
+    roots: tokio::sync::Mutex<HashMap<String, String>>,
+    next_id: AtomicU64,
+}
+
+fn validate(options: &AdvertisementMonitorOptions) -> Result<()> {
+    if options.patterns.is_empty() {
+        return Err(Error::CommandError("An or_patterns monitor needs at least one pattern".to_string()));
+    }
+    for pattern in &options.patterns {
+        if pattern.content.is_empty()
+            || pattern.start_position as usize + pattern.content.len() > AD_MAX_LEN
+        {
+            return Err(Error::CommandError(format!(
+                "Pattern for AD type 0x{:02x} must be 1-{} bytes within the advertisement",
+                pattern.ad_type, AD_MAX_LEN
+            )));
+        }
+    }
+    if options.rssi_low_threshold.is_some() != options.rssi_high_threshold.is_some() {
+        return Err(Error::CommandError(
+            "rssiLowThreshold and rssiHighThreshold must be set together".to_string(),
+        ));
+    }
+    if let (Some(low), Some(high)) = (options.rssi_low_threshold, options.rssi_high_threshold) {
+        if !(-127..=20).contains(&low) || !(-127..=20).contains(&high) || low > high {
+            return Err(Error::CommandError(format!(
+                "Invalid RSSI thresholds {}..{} (-127 to 20 dBm, low <= high)",
+                low, high
+            )));
+        }
+    }
+    Ok(())
+}
+
+async fn ensure_supported(conn: &Connection, adapter_path: &str) -> Result<()> {
+    let proxy = Proxy::new(conn, "org.bluez", adapter_path, "org.freedesktop.DBus.Properties").await?;
+    let reply = proxy
+        .call_method("GetAll", &("org.bluez.AdvertisementMonitorManager1",))
+        .await
+        .map_err(|_| {
+            Error::NotFound(format!(
+                "AdvertisementMonitorManager1 not available on {} (BlueZ 5.56+, experimental features may need bluetoothd -E)",
+                adapter_path
+            ))
+        })?;
+    let body = reply.body();
+    let props: HashMap<String, OwnedValue> = body.deserialize()?;
+    let supported: Vec<String> = match props.get("SupportedMonitorTypes").map(|v| &**v) {
+        Some(ZbusValue::Array(types)) => types.iter().filter_map(|t| String::try_from(t).ok()).collect(),
+        _ => Vec::new(),
+    };
+    if !supported.iter().any(|t| t == MONITOR_TYPE) {
+        return Err(Error::NotFound(format!("{} does not support {} monitors", adapter_path, MONITOR_TYPE)));
+    }
+    Ok(())
+}
+
+impl AdvertisementMonitors {
+    pub async fn register<R: Runtime>(
+        &self,
+        app: AppHandle<R>,
+        conn: &Connection,
+        adapter_path: &str,
+        options: AdvertisementMonitorOptions,
+    ) -> Result<String> {
+        validate(&options)?;
+        ensure_supported(conn, adapter_path).await?;
+
+        let adapter_node = adapter_path.rsplit('/').next().unwrap_or_default();
+        let root = format!("{}/{}", MONITOR_BASE_PATH, adapter_node);
+        let id = format!("monitor{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let path = format!("{}/{}", root, id);
+
+        let mut roots = self.roots.lock().await;
+        // The monitor goes first so BlueZ finds it in GetManagedObjects on a new root;
+        // on an existing root the ObjectManager announces it with InterfacesAdded
+        let monitor = Monitor {
+            app,
+            id: id.clone(),
+            options: options.clone(),
+        };
+        conn.object_server().at(path.as_str(), monitor).await?;
+
+        if !roots.contains_key(adapter_path) {
+            conn.object_server().at(root.as_str(), ObjectManager).await?;
+            let registered = async {
+                let proxy = Proxy::new(conn, "org.bluez", adapter_path, "org.bluez.AdvertisementMonitorManager1").await?;
+                proxy
+                    .call_method("RegisterMonitor", &(ObjectPath::try_from(root.as_str())?,))
+                    .await?;
+                Ok::<_, Error>(())
+            }
+            .await;
+            if let Err(e) = registered {
+                error!("Error registering advertisement monitors on {}: {}", adapter_path, e);
+                let _ = conn.object_server().remove::<Monitor<R>, _>(path.as_str()).await;
+                let _ = conn.object_server().remove::<ObjectManager, _>(root.as_str()).await;
+                return Err(e);
+            }
+            roots.insert(adapter_path.to_string(), root);
+        }
+
+        info!("Advertisement monitor {} registered on {}", id, adapter_path);
+        self.monitors.lock().unwrap().insert(id.clone(), MonitorEntry {
+            info: AdvertisementMonitorInfo {
+                id: id.clone(),
+                adapter: adapter_path.to_string(),
+                options,
+            },
+            path,
+            remove: object_remover::<Monitor<R>>(),
+        });
+        Ok(id)
+    }
+
+    pub async fn unregister(&self, conn: &Connection, id: &str) -> Result<()> {
+        let mut roots = self.roots.lock().await;
+        let entry = self
+            .monitors
+            .lock()
+            .unwrap()
+            .remove(id)
+            .ok_or_else(|| Error::NotFound(format!("Advertisement monitor not found: {}", id)))?;
+        (entry.remove)(conn.clone(), entry.path).await?;
+
+        let adapter = entry.info.adapter;
+        let last_on_adapter = !self.monitors.lock().unwrap().values().any(|m| m.info.adapter == adapter);
+        if !last_on_adapter {
+            return Ok(());
+        }
+        let Some(root) = roots.remove(&adapter) else {
+            return Ok(());
+        };
+        let proxy = Proxy::new(conn, "org.bluez", adapter.as_str(), "org.bluez.AdvertisementMonitorManager1").await?;
+        if let Err(e) = proxy
+            .call_method("UnregisterMonitor", &(ObjectPath::try_from(root.as_str())?,))
+            .await
+        {
+            // The adapter may be gone already
+            error!("Error unregistering advertisement monitors on {}: {}", adapter, e);
+        }
+        conn.object_server().remove::<ObjectManager, _>(root.as_str()).await?;
+        Ok(())
+    }
+
+    pub fn list(&self) -> Vec<AdvertisementMonitorInfo> {
+        let mut list: Vec<AdvertisementMonitorInfo> = self
+            .monitors
+            .lock()
+            .unwrap()
+            .values()
+            .map(|entry| entry.info.clone())
+            .collect();
+        list.sort_by(|a, b| a.id.cmp(&b.id));
+        list
+    }
+}
@@ -0,0 +1,236 @@
+use futures::channel::oneshot;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Runtime};
+use tracing::{error, info};
+use zbus::{zvariant::{ObjectPath, OwnedObjectPath}, Connection, DBusError, Proxy};
+
+use crate::events::{emit_change, BluetoothEvent};
+use crate::{Error, Result};
+
+pub const AGENT_PATH: &str = "/org/vasak/bluetooth/agent";
+pub const DEFAULT_AGENT_CAPABILITY: &str = "KeyboardDisplay";
+/// IO capabilities `AgentManager1.RegisterAgent` accepts.
+pub const AGENT_CAPABILITIES: [&str; 5] =
+    ["DisplayOnly", "DisplayYesNo", "KeyboardOnly", "NoInputNoOutput", "KeyboardDisplay"];
+
+/// Answer to a `pairing-request` event.
+enum PairingResponse {
+    Accept(Option<String>),
+    Reject,
+}
+
+type PendingMap = Arc<Mutex<HashMap<String, oneshot::Sender<PairingResponse>>>>;
+
+/// Pairing agent state kept inside `BluetoothManager`.
+#[derive(Default)]
+pub struct PairingAgentState {
+    pending: PendingMap,
+    registered: Mutex<bool>,
+}
+
+#[derive(DBusError, Debug)]
+#[zbus(prefix = "org.bluez.Error")]
+enum AgentError {
+    #[zbus(error)]
+    ZBus(zbus::Error),
+    Rejected(String),
+    Canceled(String),
+}
+
+struct PairingAgent<R: Runtime> {
+    app: AppHandle<R>,
+    pending: PendingMap,
+}
+
+impl<R: Runtime> PairingAgent<R> {
+    fn emit(&self, change_type: &str, data: serde_json::Value) {
+        emit_change(&self.app, BluetoothEvent::other(change_type, data));
+    }
+
+    /// Emits a `pairing-request` and waits for `pairing_respond`.
+    async fn ask(
+        &self,
+        device: &ObjectPath<'_>,
+        mut request: serde_json::Value,
+    ) -> std::result::Result<Option<String>, AgentError> {
+        let device = device.to_string();
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(device.clone(), tx);
+
+        request["device"] = serde_json::json!(device);
+        self.emit("pairing-request", request);
+
+        match rx.await {
+            Ok(PairingResponse::Accept(value)) => Ok(value),
+            Ok(PairingResponse::Reject) => Err(AgentError::Rejected("Rejected by user".to_string())),
+            Err(_) => Err(AgentError::Canceled("Request canceled".to_string())),
+        }
+    }
+}
+
+#[zbus::interface(name = "org.bluez.Agent1")]
+impl<R: Runtime> PairingAgent<R> {
+    async fn release(&self) {
+        info!("Pairing agent released by BlueZ");
+    }
+
+    async fn request_pin_code(&self, device: OwnedObjectPath) -> std::result::Result<String, AgentError> {
+        let pin = self.ask(&device, serde_json::json!({ "kind": "pin-code" })).await?;
+        pin.filter(|p| !p.is_empty())
+            .ok_or_else(|| AgentError::Rejected("No PIN code given".to_string()))
+    }
+
+    async fn display_pin_code(&self, device: OwnedObjectPath, pincode: String) {
+        self.emit("pairing-display", serde_json::json!({
+            "device": device.to_string(),
+            "kind": "pin-code",
+            "passkey": pincode,
+        }));
+    }
+
+    async fn request_passkey(&self, device: OwnedObjectPath) -> std::result::Result<u32, AgentError> {
+        let passkey = self.ask(&device, serde_json::json!({ "kind": "passkey" })).await?;
+        passkey
+            .and_then(|p| p.trim().parse::<u32>().ok())
+            .filter(|p| *p <= 999_999)
+            .ok_or_else(|| AgentError::Rejected("Invalid passkey".to_string()))
+    }
+
+    /// Keyboards: the user has to type `passkey` on the device; `entered` counts typed digits.
+    async fn display_passkey(&self, device: OwnedObjectPath, passkey: u32, entered: u16) {
+        self.emit("pairing-display", serde_json::json!({
+            "device": device.to_string(),
+            "kind": "passkey",
+            "passkey": format!("{:06}", passkey),
+            "entered": entered,
+        }));
+    }
+
+    async fn request_confirmation(
+        &self,
+        device: OwnedObjectPath,
+        passkey: u32,
+    ) -> std::result::Result<(), AgentError> {
+        self.ask(&device, serde_json::json!({
+            "kind": "confirmation",
+            "passkey": format!("{:06}", passkey),
+        }))
+        .await
+        .map(|_| ())
+    }
+
+    async fn request_authorization(&self, device: OwnedObjectPath) -> std::result::Result<(), AgentError> {
+        self.ask(&device, serde_json::json!({ "kind": "authorization" }))
+            .await
+            .map(|_| ())
+    }
+
+    async fn authorize_service(
+        &self,
+        device: OwnedObjectPath,
+        uuid: String,
+    ) -> std::result::Result<(), AgentError> {
+        self.ask(&device, serde_json::json!({ "kind": "service-authorization", "uuid": uuid }))
+            .await
+            .map(|_| ())
+    }
+
+    async fn cancel(&self) {
+        let canceled: Vec<String> = self.pending.lock().unwrap().drain().map(|(d, _)| d).collect();
+        for device in canceled {
+            self.emit("pairing-canceled", serde_json::json!({ "device": device }));
+        }
+    }
+}
+
+impl PairingAgentState {
+    /// Exports the agent on `conn` and registers it with `AgentManager1`.
+    ///
+    /// BlueZ uses the agent of the D-Bus client that calls `Device1.Pair`, so pairing
+    /// must go through the same connection.
+    pub async fn ensure_registered<R: Runtime>(
+        &self,
+        app: AppHandle<R>,
+        conn: &Connection,
+        capability: &str,
+    ) -> Result<()> {
+        if *self.registered.lock().unwrap() {
+            return Ok(());
+        }
+
+        let agent = PairingAgent {
+            app,
+            pending: self.pending.clone(),
+        };
+        conn.object_server().at(AGENT_PATH, agent).await?;
+
+        let proxy = Proxy::new(conn, "org.bluez", "/org/bluez", "org.bluez.AgentManager1").await?;
+        match proxy
+            .call_method("RegisterAgent", &(ObjectPath::try_from(AGENT_PATH)?, capability))
+            .await
+        {
+            Ok(_) => {}
+            Err(e) if e.to_string().contains("AlreadyExists") => {}
+            Err(e) => {
+                error!("Error registering pairing agent: {}", e);
+                return Err(e.into());
+            }
+        }
+
+        info!("Pairing agent registered at {} ({})", AGENT_PATH, capability);
+        *self.registered.lock().unwrap() = true;
+        Ok(())
+    }
+
+    pub fn respond(&self, device_path: &str, accept: bool, value: Option<String>) -> Result<()> {
+        let sender = self
+            .pending
+            .lock()
+            .unwrap()
+            .remove(device_path)
+            .ok_or_else(|| Error::NotFound(format!("No pending pairing request for {}", device_path)))?;
+        let response = if accept {
+            PairingResponse::Accept(value)
+        } else {
+            PairingResponse::Reject
+        };
+        sender
+            .send(response)
+            .map_err(|_| Error::CommandError("Pairing request is no longer pending".to_string()))
+    }
+}
+
+fn map_pairing_error(e: zbus::Error) -> Error {
+    let msg = e.to_string();
+    if msg.contains("Authentication") || msg.contains("ConnectionAttemptFailed") {
+        Error::PairingFailed(msg)
+    } else {
+        e.into()
+    }
+}
+
+/// Calls `Device1.Pair` on the connection that owns the pairing agent.
+pub async fn pair_device(conn: &Connection, device_path: &str) -> Result<()> {
+    let proxy = Proxy::new(conn, "org.bluez", device_path, "org.bluez.Device1").await?;
+    match proxy.call_method("Pair", &()).await {
+        Ok(_) => Ok(()),
+        Err(e) if e.to_string().contains("AlreadyExists") => {
+            info!("Device {} already paired, continuing...", device_path);
+            Ok(())
+        }
+        Err(e) => {
+            error!("Error pairing {}: {}", device_path, e);
+            Err(map_pairing_error(e))
+        }
+    }
+}
+
+pub async fn cancel_pairing(conn: &Connection, device_path: &str) -> Result<()> {
+    let proxy = Proxy::new(conn, "org.bluez", device_path, "org.bluez.Device1").await?;
+    match proxy.call_method("CancelPairing", &()).await {
+        Ok(_) => Ok(()),
+        Err(e) if e.to_string().contains("DoesNotExist") => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,217 @@
+use crate::models::BMessage;
+use crate::vcard::parse_vcards;
+use crate::{Error, Result};
+
+/// Parses a MAP bMessage (`BEGIN:BMSG` ... `END:BMSG`).
+///
+/// The vCard directly under BMSG is the originator; vCards inside BENV are recipients.
+pub fn parse_bmessage(input: &str) -> BMessage {
+    let mut message = BMessage::default();
+    let mut stack: Vec<String> = Vec::new();
+    let mut vcard_lines: Vec<&str> = Vec::new();
+    let mut body_lines: Vec<&str> = Vec::new();
+
+    for raw in input.split('\n') {
+        let line = raw.strip_suffix('\r').unwrap_or(raw);
+        let in_msg = stack.last().map(|s| s == "MSG").unwrap_or(false);
+        let in_vcard = stack.last().map(|s| s == "VCARD").unwrap_or(false);
+
+        if in_msg {
+            if line.eq_ignore_ascii_case("END:MSG") {
+                stack.pop();
+            } else {
+                body_lines.push(line);
+            }
+            continue;
+        }
+
+        if in_vcard {
+            vcard_lines.push(line);
+            if line.eq_ignore_ascii_case("END:VCARD") {
+                stack.pop();
+                let contact = parse_vcards(&vcard_lines.join("\r\n")).into_iter().next();
+                vcard_lines.clear();
+                if let Some(contact) = contact {
+                    if stack.iter().any(|s| s == "BENV") {
+                        message.recipients.push(contact);
+                    } else {
+                        message.originator = Some(contact);
+                    }
+                }
+            }
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().to_ascii_uppercase();
+        let value = value.trim();
+
+        match key.as_str() {
+            "BEGIN" => {
+                let block = value.to_ascii_uppercase();
+                if block == "VCARD" {
+                    vcard_lines.push(line);
+                }
+                stack.push(block);
+            }
+            "END" => {
+                stack.pop();
+            }
+            "VERSION" if stack.last().map(|s| s == "BMSG").unwrap_or(false) => {
+                message.version = Some(value.to_string())
+            }
+            "STATUS" => message.status = Some(value.to_string()),
+            "TYPE" => message.message_type = Some(value.to_string()),
+            "FOLDER" => message.folder = Some(value.to_string()).filter(|f| !f.is_empty()),
+            "CHARSET" => message.charset = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    message.body = body_lines.join("\n");
+    message
+}
+
+/// Lines that would end the message body early.
+const TERMINATORS: [&str; 5] = ["BEGIN:MSG", "END:MSG", "END:BBODY", "END:BENV", "END:BMSG"];
+
+/// Accepts a phone number with optional spaces, dashes, dots or parentheses; returns it
+/// with only digits, `+`, `*` and `#` left.
+fn normalize_recipient(recipient: &str) -> Result<String> {
+    let number: String = recipient
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')'))
+        .collect();
+    if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit() || matches!(c, '+' | '*' | '#')) {
+        return Err(Error::CommandError(format!("Not a phone number: {:?}", recipient)));
+    }
+    Ok(number)
+}
+
+/// Builds an SMS bMessage for `PushMessage` addressed to a single phone number.
+pub fn build_sms_bmessage(recipient: &str, text: &str) -> Result<String> {
+    let recipient = normalize_recipient(recipient)?;
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    if let Some(line) = text.lines().find(|l| TERMINATORS.iter().any(|t| l.trim().eq_ignore_ascii_case(t))) {
+        return Err(Error::CommandError(format!("Message text cannot contain a {} line", line.trim())));
+    }
+    let text = text.replace('\n', "\r\n");
+    let content = format!("BEGIN:MSG\r\n{}\r\nEND:MSG\r\n", text);
+
+    // LENGTH covers the whole content, BEGIN:MSG and END:MSG lines included
+    Ok(format!(
+        "BEGIN:BMSG\r\n\
+         VERSION:1.0\r\n\
+         STATUS:READ\r\n\
+         TYPE:SMS_GSM\r\n\
+         FOLDER:\r\n\
+         BEGIN:BENV\r\n\
+         BEGIN:VCARD\r\n\
+         VERSION:2.1\r\n\
+         N:\r\n\
+         TEL:{}\r\n\
+         END:VCARD\r\n\
+         BEGIN:BBODY\r\n\
+         CHARSET:UTF-8\r\n\
+         LENGTH:{}\r\n\
+         {}\
+         END:BBODY\r\n\
+         END:BENV\r\n\
+         END:BMSG\r\n",
+        recipient,
+        content.len(),
+        content
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_an_sms() {
+        let built = build_sms_bmessage("+34 (600) 111-222", "Hola\nqué tal\r\n\nadiós").unwrap();
+        let message = parse_bmessage(&built);
+        assert_eq!(message.version.as_deref(), Some("1.0"));
+        assert_eq!(message.status.as_deref(), Some("READ"));
+        assert_eq!(message.message_type.as_deref(), Some("SMS_GSM"));
+        assert_eq!(message.folder, None);
+        assert_eq!(message.charset.as_deref(), Some("UTF-8"));
+        assert!(message.originator.is_none());
+        assert_eq!(message.recipients.len(), 1);
+        assert_eq!(message.recipients[0].phones[0].value, "+34600111222");
+        assert_eq!(message.body, "Hola\nqué tal\n\nadiós");
+    }
+
+    #[test]
+    fn length_covers_the_message_content() {
+        let built = build_sms_bmessage("600111222", "ñandú").unwrap();
+        let length: usize = built
+            .lines()
+            .find_map(|l| l.strip_prefix("LENGTH:"))
+            .and_then(|l| l.trim().parse().ok())
+            .unwrap();
+        let start = built.find("BEGIN:MSG").unwrap();
+        let end = built.find("END:BBODY").unwrap();
+        assert_eq!(length, built[start..end].len());
+    }
+
+    #[test]
+    fn rejects_terminators_and_bad_recipients() {
+        assert!(build_sms_bmessage("600111222", "one\nEND:MSG\ntwo").is_err());
+        assert!(build_sms_bmessage("600111222", "one\r\n end:bmsg \r\ntwo").is_err());
+        assert!(build_sms_bmessage("600111222", "END:MSG is fine mid-line").is_ok());
+        assert!(build_sms_bmessage("600111222\r\nEND:VCARD", "hi").is_err());
+        assert!(build_sms_bmessage("", "hi").is_err());
+        assert!(build_sms_bmessage("*#06#", "hi").is_ok());
+    }
+
+    #[test]
+    fn parses_nested_envelopes() {
+        let input = "BEGIN:BMSG\r\n\
+                     VERSION:1.0\r\n\
+                     STATUS:UNREAD\r\n\
+                     TYPE:SMS_GSM\r\n\
+                     FOLDER:telecom/msg/inbox\r\n\
+                     BEGIN:VCARD\r\n\
+                     VERSION:2.1\r\n\
+                     N:Sender;Ann\r\n\
+                     TEL:+111\r\n\
+                     END:VCARD\r\n\
+                     BEGIN:BENV\r\n\
+                     BEGIN:VCARD\r\n\
+                     VERSION:2.1\r\n\
+                     TEL:+222\r\n\
+                     END:VCARD\r\n\
+                     BEGIN:BENV\r\n\
+                     BEGIN:VCARD\r\n\
+                     VERSION:2.1\r\n\
+                     TEL:+333\r\n\
+                     END:VCARD\r\n\
+                     BEGIN:BBODY\r\n\
+                     CHARSET:UTF-8\r\n\
+                     LENGTH:52\r\n\
+                     BEGIN:MSG\r\n\
+                     BEGIN:VCARD\r\n\
+                     VERSION:9.9\r\n\
+                     END:VCARD\r\n\
+                     END:MSG\r\n\
+                     END:BBODY\r\n\
+                     END:BENV\r\n\
+                     END:BENV\r\n\
+                     END:BMSG\r\n";
+        let message = parse_bmessage(input);
+        assert_eq!(message.version.as_deref(), Some("1.0"));
+        assert_eq!(message.status.as_deref(), Some("UNREAD"));
+        assert_eq!(message.folder.as_deref(), Some("telecom/msg/inbox"));
+        let originator = message.originator.unwrap();
+        assert_eq!(originator.formatted_name.as_deref(), Some("Ann Sender"));
+        assert_eq!(originator.phones[0].value, "+111");
+        let recipients: Vec<&str> = message.recipients.iter().map(|r| r.phones[0].value.as_str()).collect();
+        assert_eq!(recipients, vec!["+222", "+333"]);
+        // Lines inside MSG are body text, whatever they look like
+        assert_eq!(message.body, "BEGIN:VCARD\nVERSION:9.9\nEND:VCARD");
+    }
+}
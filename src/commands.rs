@@ -1,422 +1,788 @@
-use crate::models::{AdapterInfo, DeviceInfo};
+use crate::models::{
+    AdapterInfo, AdvertisementMonitorInfo, AdvertisementMonitorOptions, AutoReconnectInfo,
+    BMessage, BluetoothChange, ConnectOptions, DeviceHistory, DeviceInfo, DeviceMetadataUpdate,
+    FtpEntry, MapMessage, MapMessageFilter, ObexReceiveConfig, ObexTransfer, PhonebookEntry,
+    PresenceConfig, ProfileConnection, ProfileOptions, ProximityInfo, RfkillState,
+    SppConnectionInfo, SppOptions, SubscriptionFilter, SubscriptionInfo, ThrottleConfig,
+    VCardContact,
+};
 use crate::Result;
-use std::collections::HashMap;
-use std::convert::TryFrom;
-use tauri::State;
-use zbus::{
-    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value as ZbusValue},
-    Connection, Proxy,
+use tauri::{
+    ipc::{Channel, JavaScriptChannelId},
+    AppHandle, Runtime, State, Webview, Window,
 };
 use crate::desktop::BluetoothManager;
-use tracing::{info, error};
-
-fn get_prop_vec(props: &HashMap<String, OwnedValue>, key: &str) -> Vec<String> {
-    props.get(key).and_then(|v| {
-        match &**v {
-            ZbusValue::Array(arr) => arr.iter()
-                .map(|e| String::try_from(e).ok())
-                .collect::<Option<Vec<_>>>(),
-            _ => None,
-        }
-    }).unwrap_or_default()
-}
-
-macro_rules! get_prop {
-    ($props:expr, $key:expr, $ty:ty) => {
-        $props.get($key).and_then(|v| <$ty>::try_from(&**v).ok())
+use crate::rfkill;
+use tracing::info;
+
+#[tauri::command]
+pub async fn list_adapters(state: State<'_, BluetoothManager>) -> Result<Vec<AdapterInfo>> {
+    state.list_adapters().await
+}
+
+#[tauri::command]
+pub async fn set_adapter_powered(
+    state: State<'_, BluetoothManager>,
+    adapter_path: String,
+    powered: bool,
+) -> Result<()> {
+    state.set_adapter_powered(&adapter_path, powered).await
+}
+
+#[tauri::command]
+pub async fn get_default_adapter(state: State<'_, BluetoothManager>) -> Result<Option<AdapterInfo>> {
+    state.get_default_adapter().await
+}
+
+/// Remembers `adapter` (object path or address) as the default; `None` clears the choice.
+#[tauri::command]
+pub async fn set_default_adapter<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, BluetoothManager>,
+    adapter: Option<String>,
+) -> Result<()> {
+    state.set_default_adapter(app, adapter).await
+}
+
+#[tauri::command]
+pub async fn get_rfkill_state() -> Result<Vec<RfkillState>> {
+    Ok(rfkill::list())
+}
+
+#[tauri::command]
+pub async fn rfkill_block(
+    state: State<'_, BluetoothManager>,
+    adapter_path: Option<String>,
+) -> Result<()> {
+    let adapter_path = match adapter_path {
+        Some(path) => Some(state.default_adapter.resolve(path).await?),
+        None => None,
     };
-    ($props:expr, $key:expr, $ty:ty, $default:expr) => {
-        $props.get($key).and_then(|v| <$ty>::try_from(&**v).ok()).unwrap_or($default)
+    info!("rfkill block: {}", adapter_path.as_deref().unwrap_or("all"));
+    rfkill::set_blocked(adapter_path.as_deref(), true)
+}
+
+#[tauri::command]
+pub async fn rfkill_unblock(
+    state: State<'_, BluetoothManager>,
+    adapter_path: Option<String>,
+) -> Result<()> {
+    let adapter_path = match adapter_path {
+        Some(path) => Some(state.default_adapter.resolve(path).await?),
+        None => None,
     };
+    info!("rfkill unblock: {}", adapter_path.as_deref().unwrap_or("all"));
+    rfkill::set_blocked(adapter_path.as_deref(), false)
+}
+
+#[tauri::command]
+pub async fn get_adapter_state(
+    state: State<'_, BluetoothManager>,
+    adapter_path: String,
+) -> Result<AdapterInfo> {
+    state.get_adapter_state(&adapter_path).await
+}
+
+/// With `on_event`, the results of this scan are streamed to that Channel: the cached
+/// devices first, then `found`/`updated`/`lost` with sequence numbers, until `stop_scan`.
+#[tauri::command]
+pub async fn start_scan<R: Runtime>(
+    webview: Webview<R>,
+    state: State<'_, BluetoothManager>,
+    adapter_path: String,
+    on_event: Option<JavaScriptChannelId>,
+    stale_timeout_ms: Option<u64>,
+) -> Result<()> {
+    let channel = on_event.map(|id| id.channel_on(webview));
+    state.start_scan(&adapter_path, channel, stale_timeout_ms).await
+}
+
+#[tauri::command]
+pub async fn stop_scan(
+    state: State<'_, BluetoothManager>,
+    adapter_path: String,
+) -> Result<()> {
+    state.stop_scan(&adapter_path).await
+}
+
+#[tauri::command]
+pub async fn list_devices(
+    state: State<'_, BluetoothManager>,
+    adapter_path: String,
+) -> Result<Vec<DeviceInfo>> {
+    state.list_devices(&adapter_path).await
+}
+
+#[tauri::command]
+pub async fn get_device_info(
+    state: State<'_, BluetoothManager>,
+    device_path: String,
+) -> Result<DeviceInfo> {
+    state.get_device_info(&device_path).await
+}
+
+/// Current object path of the device with `address`. `adapter_hint` (path, address or
+/// "default") picks the copy on that adapter when several adapters know the device.
+#[tauri::command]
+pub async fn resolve_device_path(
+    state: State<'_, BluetoothManager>,
+    address: String,
+    adapter_hint: Option<String>,
+) -> Result<String> {
+    state.resolve_device_path(&address, adapter_hint.as_deref()).await
+}
+
+#[tauri::command]
+pub async fn get_device_by_address(
+    state: State<'_, BluetoothManager>,
+    address: String,
+    adapter_hint: Option<String>,
+) -> Result<DeviceInfo> {
+    state.get_device_by_address(&address, adapter_hint.as_deref()).await
+}
+
+/// Remembered devices, most recently seen first. BlueZ may no longer know some of them.
+#[tauri::command]
+pub async fn list_device_history(
+    state: State<'_, BluetoothManager>,
+    favorites_only: Option<bool>,
+    tag: Option<String>,
+) -> Result<Vec<DeviceHistory>> {
+    Ok(state.device_history.list(favorites_only.unwrap_or(false), tag.as_deref()))
+}
+
+#[tauri::command]
+pub async fn get_device_history(
+    state: State<'_, BluetoothManager>,
+    address: String,
+) -> Result<Option<DeviceHistory>> {
+    state.device_history.get(&address)
+}
+
+#[tauri::command]
+pub async fn set_device_metadata(
+    state: State<'_, BluetoothManager>,
+    address: String,
+    metadata: DeviceMetadataUpdate,
+) -> Result<DeviceHistory> {
+    state.device_history.update(&address, metadata)
+}
+
+#[tauri::command]
+pub async fn forget_device_history(state: State<'_, BluetoothManager>, address: String) -> Result<()> {
+    state.device_history.forget(&address)
+}
+
+/// Presence state (near/far/gone), smoothed RSSI and estimated distance of a device.
+#[tauri::command]
+pub async fn get_device_proximity(
+    state: State<'_, BluetoothManager>,
+    address: String,
+) -> Result<Option<ProximityInfo>> {
+    state.presence.get(&address)
+}
+
+#[tauri::command]
+pub async fn list_device_proximity(state: State<'_, BluetoothManager>) -> Result<Vec<ProximityInfo>> {
+    Ok(state.presence.list())
+}
+
+#[tauri::command]
+pub async fn get_presence_config(state: State<'_, BluetoothManager>) -> Result<PresenceConfig> {
+    Ok(state.presence.config())
+}
+
+#[tauri::command]
+pub async fn set_presence_config(
+    state: State<'_, BluetoothManager>,
+    config: PresenceConfig,
+) -> Result<()> {
+    state.presence.set_config(config)
+}
+
+#[tauri::command]
+pub async fn get_event_throttle(state: State<'_, BluetoothManager>) -> Result<ThrottleConfig> {
+    Ok(state.throttle.config())
+}
+
+/// Applies to properties changing from now on; held values keep their due time.
+#[tauri::command]
+pub async fn set_event_throttle(
+    state: State<'_, BluetoothManager>,
+    config: ThrottleConfig,
+) -> Result<()> {
+    state.throttle.set_config(config);
+    Ok(())
+}
+
+/// Delivers the `bluetooth-change` events matching `filter` to this window through
+/// `on_event`, until `unsubscribe_events` or the window closes.
+#[tauri::command]
+pub async fn subscribe_events<R: Runtime>(
+    window: Window<R>,
+    state: State<'_, BluetoothManager>,
+    filter: Option<SubscriptionFilter>,
+    on_event: Channel<BluetoothChange>,
+) -> Result<u64> {
+    Ok(state.subscriptions.subscribe(&window, filter.unwrap_or_default(), on_event))
+}
+
+#[tauri::command]
+pub async fn unsubscribe_events<R: Runtime>(
+    window: Window<R>,
+    state: State<'_, BluetoothManager>,
+    id: u64,
+) -> Result<()> {
+    state.subscriptions.unsubscribe(window.label(), id)
+}
+
+#[tauri::command]
+pub async fn list_event_subscriptions(
+    state: State<'_, BluetoothManager>,
+) -> Result<Vec<SubscriptionInfo>> {
+    Ok(state.subscriptions.list())
+}
+
+/// Passive scanning: BlueZ reports devices whose advertisements match one of the
+/// patterns without `Discovering` being on.
+#[tauri::command]
+pub async fn register_advertisement_monitor<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, BluetoothManager>,
+    adapter_path: String,
+    options: AdvertisementMonitorOptions,
+) -> Result<String> {
+    let adapter_path = state.default_adapter.resolve(adapter_path).await?;
+    state
+        .adv_monitors
+        .register(app, &state.conn, &adapter_path, options)
+        .await
+}
+
+#[tauri::command]
+pub async fn unregister_advertisement_monitor(
+    state: State<'_, BluetoothManager>,
+    id: String,
+) -> Result<()> {
+    state.adv_monitors.unregister(&state.conn, &id).await
+}
+
+#[tauri::command]
+pub async fn list_advertisement_monitors(
+    state: State<'_, BluetoothManager>,
+) -> Result<Vec<AdvertisementMonitorInfo>> {
+    Ok(state.adv_monitors.list())
+}
+
+#[tauri::command]
+pub async fn list_paired_devices(
+    state: State<'_, BluetoothManager>,
+    adapter_path: String,
+) -> Result<Vec<DeviceInfo>> {
+    state.list_paired_devices(&adapter_path).await
+}
+
+#[tauri::command]
+pub async fn connect_device(state: State<'_, BluetoothManager>, device_path: String) -> Result<()> {
+    state.connect_device(&device_path).await
+}
+
+/// `connect_device` with a timeout, retries and cancellation (`cancel_connect`); unlike it,
+/// fails unless the device really ends up connected.
+#[tauri::command]
+pub async fn connect_device_with_options<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, BluetoothManager>,
+    device_path: String,
+    options: Option<ConnectOptions>,
+) -> Result<DeviceInfo> {
+    state
+        .connect_device_with_options(app, &device_path, options.unwrap_or_default())
+        .await
+}
+
+#[tauri::command]
+pub async fn cancel_connect(state: State<'_, BluetoothManager>, id: String) -> Result<()> {
+    state.cancel_connect(&id)
+}
+
+#[tauri::command]
+pub async fn disconnect_device<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, BluetoothManager>,
+    device_path: String,
+) -> Result<()> {
+    state.disconnect_device(app, &device_path).await
+}
+
+/// Reconnect `address` automatically when it drops, comes back in range or its adapter
+/// powers on. Persisted in the app config dir.
+#[tauri::command]
+pub async fn set_auto_reconnect<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, BluetoothManager>,
+    address: String,
+    enabled: bool,
+) -> Result<()> {
+    state.auto_reconnect.set(&app, &address, enabled)
+}
+
+#[tauri::command]
+pub async fn list_auto_reconnect(state: State<'_, BluetoothManager>) -> Result<Vec<AutoReconnectInfo>> {
+    Ok(state.auto_reconnect.list())
+}
+
+#[tauri::command]
+pub async fn pair_device<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, BluetoothManager>,
+    device_path: String,
+) -> Result<()> {
+    state.pair_device(app, &device_path).await
+}
+
+#[tauri::command]
+pub async fn cancel_pairing(state: State<'_, BluetoothManager>, device_path: String) -> Result<()> {
+    state.cancel_pairing(&device_path).await
+}
+
+#[tauri::command]
+pub async fn pairing_respond(
+    state: State<'_, BluetoothManager>,
+    device_path: String,
+    accept: bool,
+    value: Option<String>,
+) -> Result<()> {
+    state.pairing_respond(&device_path, accept, value)
+}
+
+#[tauri::command]
+pub async fn set_device_trusted(
+    state: State<'_, BluetoothManager>,
+    device_path: String,
+    trusted: bool,
+) -> Result<()> {
+    state.set_device_trusted(&device_path, trusted).await
+}
+
+#[tauri::command]
+pub async fn setup_hid_device<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, BluetoothManager>,
+    device_path: String,
+) -> Result<DeviceInfo> {
+    state.setup_hid_device(app, &device_path).await
+}
+
+#[tauri::command]
+pub async fn connect_profile(
+    state: State<'_, BluetoothManager>,
+    device_path: String,
+    uuid: String,
+) -> Result<()> {
+    state.connect_profile(&device_path, &uuid).await
+}
+
+#[tauri::command]
+pub async fn disconnect_profile(
+    state: State<'_, BluetoothManager>,
+    device_path: String,
+    uuid: String,
+) -> Result<()> {
+    state.disconnect_profile(&device_path, &uuid).await
+}
+
+/// Connects to a device's PAN service and returns the created interface name (e.g. "bnep0").
+#[tauri::command]
+pub async fn network_connect(
+    state: State<'_, BluetoothManager>,
+    device_path: String,
+    role: String,
+) -> Result<String> {
+    state.network_connect(&device_path, &role).await
+}
+
+#[tauri::command]
+pub async fn network_disconnect(
+    state: State<'_, BluetoothManager>,
+    device_path: String,
+) -> Result<()> {
+    state.network_disconnect(&device_path).await
+}
+
+/// Shares this machine's connection through `bridge` (`NetworkServer1.Register`).
+#[tauri::command]
+pub async fn network_server_register(
+    state: State<'_, BluetoothManager>,
+    adapter_path: String,
+    role: String,
+    bridge: String,
+) -> Result<()> {
+    state.network_server_register(adapter_path, &role, &bridge).await
+}
+
+#[tauri::command]
+pub async fn network_server_unregister(
+    state: State<'_, BluetoothManager>,
+    adapter_path: String,
+    role: String,
+) -> Result<()> {
+    state.network_server_unregister(adapter_path, &role).await
+}
+
+#[tauri::command]
+pub async fn register_profile<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, BluetoothManager>,
+    options: ProfileOptions,
+) -> Result<String> {
+    info!("Registering custom profile: {}", options.uuid);
+    state.custom_profiles.register(app, &state.conn, options).await
+}
+
+#[tauri::command]
+pub async fn unregister_profile(
+    state: State<'_, BluetoothManager>,
+    uuid: String,
+) -> Result<()> {
+    info!("Unregistering custom profile: {}", uuid);
+    state.custom_profiles.unregister(&state.conn, &uuid).await
+}
+
+#[tauri::command]
+pub async fn list_profile_connections(
+    state: State<'_, BluetoothManager>,
+) -> Result<Vec<ProfileConnection>> {
+    Ok(state.custom_profiles.list_connections())
+}
+
+#[tauri::command]
+pub async fn profile_write(
+    state: State<'_, BluetoothManager>,
+    id: String,
+    data: Vec<u8>,
+) -> Result<()> {
+    state.custom_profiles.write(&id, &data).await
 }
 
 #[tauri::command]
-pub async fn list_adapters() -> Result<Vec<AdapterInfo>> {
-    let conn = Connection::system().await?;
-    let proxy = Proxy::new(
-        &conn,
-        "org.bluez",
-        "/",
-        "org.freedesktop.DBus.ObjectManager",
-    )
-    .await?;
-
-    let reply_message = proxy.call_method("GetManagedObjects", &()).await?;
-
-    // Decodificar correctamente como HashMap<ObjectPath, HashMap<String, HashMap<String, OwnedValue>>>
-    let managed_objects: HashMap<OwnedObjectPath, HashMap<String, HashMap<String, OwnedValue>>> =
-        reply_message.body().deserialize()?;
-
-    let mut adapters = Vec::new();
-
-    for (object_path, interfaces) in managed_objects {
-        if let Some(props) = interfaces.get("org.bluez.Adapter1") {
-            adapters.push(AdapterInfo {
-                path: object_path.to_string(),
-                address: get_prop!(props, "Address", String, String::new()),
-                name: get_prop!(props, "Name", String, String::new()),
-                alias: get_prop!(props, "Alias", String, String::new()),
-                class: get_prop!(props, "Class", u32, 0),
-                powered: get_prop!(props, "Powered", bool, false),
-                discoverable: get_prop!(props, "Discoverable", bool, false),
-                discoverable_timeout: get_prop!(props, "DiscoverableTimeout", u32, 0),
-                pairable: get_prop!(props, "Pairable", bool, false),
-                pairable_timeout: get_prop!(props, "PairableTimeout", u32, 0),
-                discovering: get_prop!(props, "Discovering", bool, false),
-                uuids: get_prop_vec(props, "UUIDs"),
-                modalias: get_prop!(props, "Modalias", String),
-            });
-        }
-    }
-    Ok(adapters)
-}
-
-#[tauri::command]
-pub async fn set_adapter_powered(adapter_path: String, powered: bool) -> Result<()> {
-    let conn = Connection::system().await?;
-    let proxy = Proxy::new(
-        &conn,
-        "org.bluez",
-        adapter_path.as_str(),
-        "org.freedesktop.DBus.Properties",
-    )
-    .await?;
-
-    proxy
-        .call_method(
-            "Set",
-            &("org.bluez.Adapter1", "Powered", ZbusValue::from(powered)),
+pub async fn profile_close(state: State<'_, BluetoothManager>, id: String) -> Result<()> {
+    info!("Closing profile connection: {}", id);
+    state.custom_profiles.close(&id)
+}
+
+#[tauri::command]
+pub async fn spp_connect<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, BluetoothManager>,
+    device_address: String,
+    options: Option<SppOptions>,
+) -> Result<String> {
+    state
+        .spp
+        .connect(
+            app,
+            &state.conn,
+            &state.custom_profiles,
+            &device_address,
+            options.unwrap_or_default(),
         )
-        .await?;
+        .await
+}
+
+#[tauri::command]
+pub async fn spp_write(state: State<'_, BluetoothManager>, id: String, data: Vec<u8>) -> Result<()> {
+    state.spp.write(&id, &data).await
+}
+
+#[tauri::command]
+pub async fn spp_pause(state: State<'_, BluetoothManager>, id: String) -> Result<()> {
+    state.spp.set_paused(&id, true)
+}
+
+#[tauri::command]
+pub async fn spp_resume(state: State<'_, BluetoothManager>, id: String) -> Result<()> {
+    state.spp.set_paused(&id, false)
+}
+
+#[tauri::command]
+pub async fn spp_close(state: State<'_, BluetoothManager>, id: String) -> Result<()> {
+    info!("Closing SPP connection: {}", id);
+    state.spp.close(&id)
+}
+
+#[tauri::command]
+pub async fn list_spp_connections(
+    state: State<'_, BluetoothManager>,
+) -> Result<Vec<SppConnectionInfo>> {
+    Ok(state.spp.list())
+}
+
+#[tauri::command]
+pub async fn send_file<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, BluetoothManager>,
+    device_address: String,
+    file_path: String,
+) -> Result<ObexTransfer> {
+    state.send_file(app, device_address, file_path).await
+}
+
+#[tauri::command]
+pub async fn cancel_transfer(state: State<'_, BluetoothManager>, transfer_path: String) -> Result<()> {
+    info!("Cancelling OBEX transfer: {}", transfer_path);
+    state.obex_transfers.cancel(&transfer_path).await
+}
+
+#[tauri::command]
+pub async fn register_obex_agent<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, BluetoothManager>,
+) -> Result<()> {
+    state.obex_agent.register(app).await
+}
+
+#[tauri::command]
+pub async fn unregister_obex_agent(state: State<'_, BluetoothManager>) -> Result<()> {
+    state.obex_agent.unregister().await
+}
+
+#[tauri::command]
+pub async fn accept_incoming_transfer(
+    state: State<'_, BluetoothManager>,
+    transfer_path: String,
+    target_dir: Option<String>,
+) -> Result<()> {
+    info!("Accepting incoming OBEX transfer: {}", transfer_path);
+    state.obex_agent.accept(&transfer_path, target_dir)
+}
+
+#[tauri::command]
+pub async fn reject_incoming_transfer(
+    state: State<'_, BluetoothManager>,
+    transfer_path: String,
+) -> Result<()> {
+    info!("Rejecting incoming OBEX transfer: {}", transfer_path);
+    state.obex_agent.reject(&transfer_path)
+}
+
+#[tauri::command]
+pub async fn get_obex_receive_config(
+    state: State<'_, BluetoothManager>,
+) -> Result<ObexReceiveConfig> {
+    Ok(state.obex_agent.config())
+}
+
+#[tauri::command]
+pub async fn set_obex_receive_config(
+    state: State<'_, BluetoothManager>,
+    config: ObexReceiveConfig,
+) -> Result<()> {
+    state.obex_agent.set_config(config);
     Ok(())
 }
 
 #[tauri::command]
-pub async fn get_adapter_state(adapter_path: String) -> Result<AdapterInfo> {
-    let conn = Connection::system().await?;
-    let proxy = Proxy::new(
-        &conn,
-        "org.bluez",
-        adapter_path.as_str(),
-        "org.freedesktop.DBus.Properties",
-    )
-    .await?;
-
-    let reply_message = proxy
-        .call_method("GetAll", &("org.bluez.Adapter1",))
-        .await?;
-
-    let props: HashMap<String, OwnedValue> = reply_message.body().deserialize()?;
-
-    Ok(AdapterInfo {
-        path: adapter_path,
-        address: get_prop!(props, "Address", String, String::new()),
-        name: get_prop!(props, "Name", String, String::new()),
-        alias: get_prop!(props, "Alias", String, String::new()),
-        class: get_prop!(props, "Class", u32, 0),
-        powered: get_prop!(props, "Powered", bool, false),
-        discoverable: get_prop!(props, "Discoverable", bool, false),
-        discoverable_timeout: get_prop!(props, "DiscoverableTimeout", u32, 0),
-        pairable: get_prop!(props, "Pairable", bool, false),
-        pairable_timeout: get_prop!(props, "PairableTimeout", u32, 0),
-        discovering: get_prop!(props, "Discovering", bool, false),
-        uuids: get_prop_vec(&props, "UUIDs"),
-        modalias: get_prop!(props, "Modalias", String),
-    })
-}
-
-#[tauri::command]
-pub async fn start_scan(adapter_path: String) -> Result<()> {
-    info!("Starting scan on adapter: {}", adapter_path);
-    
-    let conn = Connection::system().await?;
-    let proxy = Proxy::new(
-        &conn,
-        "org.bluez",
-        adapter_path.as_str(),
-        "org.bluez.Adapter1",
-    )
-    .await?;
-    
-    match proxy.call_method("StartDiscovery", &()).await {
-        Ok(_) => {
-            info!("Scan started successfully");
-            Ok(())
-        }
-        Err(e) => {
-            let msg = e.to_string();
-            error!("StartDiscovery error: {}", msg);
-            if msg.contains("org.bluez.Error.InProgress") || msg.contains("InProgress") {
-                info!("Scan already in progress, continuing...");
-                Ok(())
-            } else {
-                error!("Error starting scan: {}", msg);
-                Err(e.into())
-            }
-        }
-    }
-}
-
-#[tauri::command]
-pub async fn stop_scan(adapter_path: String) -> Result<()> {
-    info!("Stopping scan on adapter: {}", adapter_path);
-    
-    let conn = Connection::system().await?;
-    let proxy = Proxy::new(
-        &conn,
-        "org.bluez",
-        adapter_path.as_str(),
-        "org.bluez.Adapter1",
-    )
-    .await?;
-    
-    match proxy.call_method("StopDiscovery", &()).await {
-        Ok(_) => {
-            info!("Scan stopped successfully");
-            Ok(())
-        }
-        Err(e) => {
-            let msg = e.to_string();
-            error!("StopDiscovery error: {}", msg);
-            if msg.contains("No discovery started") 
-                || msg.contains("org.bluez.Error.Failed")
-                || msg.contains("org.bluez.Error.NotReady") {
-                info!("No active scan to stop, continuing...");
-                Ok(())
-            } else {
-                error!("Error stopping scan: {}", msg);
-                Err(e.into())
-            }
-        }
-    }
-}
-
-#[tauri::command]
-pub async fn list_devices(adapter_path: String) -> Result<Vec<DeviceInfo>> {
-    info!("Listing devices for adapter: {}", adapter_path);
-    
-    let conn = Connection::system().await?;
-    let object_manager_proxy = Proxy::new(
-        &conn,
-        "org.bluez",
-        "/",
-        "org.freedesktop.DBus.ObjectManager",
-    )
-    .await?;
-
-    let reply_message = object_manager_proxy
-        .call_method("GetManagedObjects", &())
-        .await?;
-
-    let managed_objects: HashMap<OwnedObjectPath, HashMap<String, HashMap<String, OwnedValue>>> =
-        reply_message.body().deserialize()?;
-
-    info!("Total managed objects: {}", managed_objects.len());
-
-    let mut devices = Vec::new();
-
-    for (object_path, interfaces) in managed_objects {
-        let path_str = object_path.as_str();
-        let has_device_interface = interfaces.contains_key("org.bluez.Device1");
-        
-        info!(
-            "Object path: {} | has Device1: {} | starts_with adapter: {}",
-            path_str,
-            has_device_interface,
-            path_str.starts_with(&adapter_path)
-        );
-        
-        if path_str.starts_with(&adapter_path) {
-            if let Some(props) = interfaces.get("org.bluez.Device1") {
-                let device_name = get_prop!(props, "Name", String, "Unknown".to_string());
-                let device_address = get_prop!(props, "Address", String, "Unknown".to_string());
-
-                info!("Found device: {} ({})", device_name, device_address);
-
-                devices.push(DeviceInfo {
-                    path: object_path.to_string(),
-                    address: device_address,
-                    name: get_prop!(props, "Name", String),
-                    alias: get_prop!(props, "Alias", String),
-                    class: get_prop!(props, "Class", u32),
-                    appearance: get_prop!(props, "Appearance", u16),
-                    icon: get_prop!(props, "Icon", String),
-                    paired: get_prop!(props, "Paired", bool, false),
-                    trusted: get_prop!(props, "Trusted", bool, false),
-                    blocked: get_prop!(props, "Blocked", bool, false),
-                    legacy_pairing: get_prop!(props, "LegacyPairing", bool, false),
-                    rssi: get_prop!(props, "RSSI", i16),
-                    tx_power: get_prop!(props, "TxPower", i16),
-                    connected: get_prop!(props, "Connected", bool, false),
-                    uuids: get_prop_vec(props, "UUIDs"),
-                    adapter: props
-                        .get("Adapter")
-                        .and_then(|v| ObjectPath::try_from(&**v).ok())
-                        .map(|p: ObjectPath| p.to_string())
-                        .unwrap_or_default(),
-                    services_resolved: get_prop!(props, "ServicesResolved", bool, false),
-                });
-            }
-        }
-    }
-    
-    info!("Total devices found: {}", devices.len());
-    Ok(devices)
-}
-
-#[tauri::command]
-pub async fn get_device_info(device_path: String) -> Result<DeviceInfo> {
-    let conn = Connection::system().await?;
-    let proxy = Proxy::new(
-        &conn,
-        "org.bluez",
-        device_path.as_str(),
-        "org.freedesktop.DBus.Properties",
-    )
-    .await?;
-
-    let reply_message = proxy.call_method("GetAll", &("org.bluez.Device1",)).await?;
-
-    let props: HashMap<String, OwnedValue> = reply_message.body().deserialize()?;
-
-    Ok(DeviceInfo {
-        path: device_path,
-        address: get_prop!(props, "Address", String, String::new()),
-        name: get_prop!(props, "Name", String),
-        alias: get_prop!(props, "Alias", String),
-        class: get_prop!(props, "Class", u32),
-        appearance: get_prop!(props, "Appearance", u16),
-        icon: get_prop!(props, "Icon", String),
-        paired: get_prop!(props, "Paired", bool, false),
-        trusted: get_prop!(props, "Trusted", bool, false),
-        blocked: get_prop!(props, "Blocked", bool, false),
-        legacy_pairing: get_prop!(props, "LegacyPairing", bool, false),
-        rssi: get_prop!(props, "RSSI", i16),
-        tx_power: get_prop!(props, "TxPower", i16),
-        connected: get_prop!(props, "Connected", bool, false),
-        uuids: get_prop_vec(&props, "UUIDs"),
-        adapter: props
-            .get("Adapter")
-            .and_then(|v| ObjectPath::try_from(&**v).ok())
-            .map(|p: ObjectPath| p.to_string())
-            .unwrap_or_default(),
-        services_resolved: get_prop!(props, "ServicesResolved", bool, false),
-    })
-}
-
-#[tauri::command]
-pub async fn list_paired_devices(adapter_path: String) -> Result<Vec<DeviceInfo>> {
-    let conn = Connection::system().await?;
-    let object_manager_proxy = Proxy::new(
-        &conn,
-        "org.bluez",
-        "/",
-        "org.freedesktop.DBus.ObjectManager",
-    )
-    .await?;
-
-    let reply_message = object_manager_proxy
-        .call_method("GetManagedObjects", &())
-        .await?;
-
-    // Decodificar correctamente como HashMap
-    let managed_objects: HashMap<OwnedObjectPath, HashMap<String, HashMap<String, OwnedValue>>> =
-        reply_message.body().deserialize()?;
-
-    let mut paired_devices = Vec::new();
-
-    for (object_path, interfaces) in managed_objects {
-        if object_path.as_str().starts_with(&adapter_path) {
-            if let Some(props) = interfaces.get("org.bluez.Device1") {
-                let paired = get_prop!(props, "Paired", bool, false);
-                if paired {
-                    paired_devices.push(DeviceInfo {
-                        path: object_path.to_string(),
-                        address: get_prop!(props, "Address", String, String::new()),
-                        name: get_prop!(props, "Name", String),
-                        alias: get_prop!(props, "Alias", String),
-                        class: get_prop!(props, "Class", u32),
-                        appearance: get_prop!(props, "Appearance", u16),
-                        icon: get_prop!(props, "Icon", String),
-                        paired,
-                        trusted: get_prop!(props, "Trusted", bool, false),
-                        blocked: get_prop!(props, "Blocked", bool, false),
-                        legacy_pairing: get_prop!(props, "LegacyPairing", bool, false),
-                        rssi: get_prop!(props, "RSSI", i16),
-                        tx_power: get_prop!(props, "TxPower", i16),
-                        connected: get_prop!(props, "Connected", bool, false),
-                        uuids: get_prop_vec(props, "UUIDs"),
-                        adapter: props
-                            .get("Adapter")
-                            .and_then(|v| ObjectPath::try_from(&**v).ok())
-                            .map(|p: ObjectPath| p.to_string())
-                            .unwrap_or_default(),
-                        services_resolved: get_prop!(props, "ServicesResolved", bool, false),
-                    });
-                }
-            }
-        }
-    }
-    Ok(paired_devices)
-}
-
-#[tauri::command]
-pub async fn connect_device(device_path: String) -> Result<()> {
-    let conn = Connection::system().await?;
-    let proxy = Proxy::new(
-        &conn,
-        "org.bluez",
-        device_path.as_str(),
-        "org.bluez.Device1",
-    )
-    .await?;
-
-    match proxy.call_method("Connect", &()).await {
-        Ok(_) => Ok(()),
-        Err(e) => {
-            let msg = e.to_string();
-            if msg.contains("InProgress")
-                || msg.contains("br-connection-busy")
-                || msg.contains("AlreadyConnected")
-                || msg.contains("br-connection-already-connected")
-            {
-                info!("Device already connecting or connected, continuing...");
-                Ok(())
-            } else {
-                error!("Error connecting to device: {}", msg);
-                Err(e.into())
-            }
-        }
-    }
-}
-
-#[tauri::command]
-pub async fn disconnect_device(device_path: String) -> Result<()> {
-    let conn = Connection::system().await?;
-    let proxy = Proxy::new(
-        &conn,
-        "org.bluez",
-        device_path.as_str(),
-        "org.bluez.Device1",
-    )
-    .await?;
-
-    match proxy.call_method("Disconnect", &()).await {
-        Ok(_) => Ok(()),
-        Err(e) => {
-            let msg = e.to_string();
-            if msg.contains("NotConnected")
-                || msg.contains("br-connection-not-connected")
-                || msg.contains("br-connection-already-disconnected")
-            {
-                info!("Device already disconnected, continuing...");
-                Ok(())
-            } else {
-                error!("Error disconnecting device: {}", msg);
-                Err(e.into())
-            }
-        }
-    }
+pub async fn ftp_connect<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, BluetoothManager>,
+    device_address: String,
+) -> Result<String> {
+    info!("Opening OBEX FTP session with {}", device_address);
+    state.ftp_sessions.connect(app, &device_address).await
+}
+
+#[tauri::command]
+pub async fn ftp_disconnect(state: State<'_, BluetoothManager>, session: String) -> Result<()> {
+    info!("Closing OBEX FTP session {}", session);
+    state.ftp_sessions.disconnect(&session).await
+}
+
+#[tauri::command]
+pub async fn ftp_list_folder(
+    state: State<'_, BluetoothManager>,
+    session: String,
+) -> Result<Vec<FtpEntry>> {
+    state.ftp_sessions.list_folder(&session).await
+}
+
+#[tauri::command]
+pub async fn ftp_change_folder(
+    state: State<'_, BluetoothManager>,
+    session: String,
+    folder: String,
+) -> Result<()> {
+    state.ftp_sessions.change_folder(&session, &folder).await
+}
+
+#[tauri::command]
+pub async fn ftp_create_folder(
+    state: State<'_, BluetoothManager>,
+    session: String,
+    folder: String,
+) -> Result<()> {
+    state.ftp_sessions.create_folder(&session, &folder).await
+}
+
+#[tauri::command]
+pub async fn ftp_delete(
+    state: State<'_, BluetoothManager>,
+    session: String,
+    name: String,
+) -> Result<()> {
+    info!("Deleting {} over OBEX FTP session {}", name, session);
+    state.ftp_sessions.delete(&session, &name).await
+}
+
+#[tauri::command]
+pub async fn ftp_get_file<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, BluetoothManager>,
+    session: String,
+    remote_name: String,
+    local_path: String,
+) -> Result<ObexTransfer> {
+    state
+        .ftp_sessions
+        .get_file(app, &session, &remote_name, &local_path)
+        .await
+}
+
+#[tauri::command]
+pub async fn ftp_put_file<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, BluetoothManager>,
+    session: String,
+    local_path: String,
+    remote_name: String,
+) -> Result<ObexTransfer> {
+    state
+        .ftp_sessions
+        .put_file(app, &session, &local_path, &remote_name)
+        .await
+}
+
+#[tauri::command]
+pub async fn pbap_list(
+    state: State<'_, BluetoothManager>,
+    device_address: String,
+    location: String,
+    phonebook: String,
+    offset: Option<u16>,
+    max_count: Option<u16>,
+) -> Result<Vec<PhonebookEntry>> {
+    state
+        .pbap_list(&device_address, &location, &phonebook, offset, max_count)
+        .await
+}
+
+#[tauri::command]
+pub async fn pbap_pull_all<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, BluetoothManager>,
+    device_address: String,
+    location: String,
+    phonebook: String,
+    offset: Option<u16>,
+    max_count: Option<u16>,
+) -> Result<Vec<VCardContact>> {
+    state
+        .pbap_pull_all(&app, &device_address, &location, &phonebook, offset, max_count)
+        .await
+}
+
+#[tauri::command]
+pub async fn pbap_pull<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, BluetoothManager>,
+    device_address: String,
+    location: String,
+    phonebook: String,
+    handle: String,
+) -> Result<VCardContact> {
+    state
+        .pbap_pull(&app, &device_address, &location, &phonebook, &handle)
+        .await
+}
+
+#[tauri::command]
+pub async fn map_connect<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, BluetoothManager>,
+    device_address: String,
+) -> Result<()> {
+    info!("Opening MAP session with {}", device_address);
+    state.map_sessions.connect(app, &device_address).await
+}
+
+#[tauri::command]
+pub async fn map_disconnect(
+    state: State<'_, BluetoothManager>,
+    device_address: String,
+) -> Result<()> {
+    info!("Closing MAP session with {}", device_address);
+    state.map_sessions.disconnect(&device_address).await
+}
+
+#[tauri::command]
+pub async fn map_list_folders(
+    state: State<'_, BluetoothManager>,
+    device_address: String,
+    folder: Option<String>,
+) -> Result<Vec<String>> {
+    let folder = folder.unwrap_or_else(|| "/telecom/msg".to_string());
+    state.map_sessions.list_folders(&device_address, &folder).await
+}
+
+#[tauri::command]
+pub async fn map_list_messages(
+    state: State<'_, BluetoothManager>,
+    device_address: String,
+    folder: String,
+    filter: Option<MapMessageFilter>,
+) -> Result<Vec<MapMessage>> {
+    state
+        .map_sessions
+        .list_messages(&device_address, &folder, filter.unwrap_or_default())
+        .await
+}
+
+#[tauri::command]
+pub async fn map_get_message<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, BluetoothManager>,
+    device_address: String,
+    message_path: String,
+) -> Result<BMessage> {
+    state.map_sessions.get_message(&app, &device_address, &message_path).await
+}
+
+#[tauri::command]
+pub async fn map_set_message_read(
+    state: State<'_, BluetoothManager>,
+    device_address: String,
+    message_path: String,
+    read: bool,
+) -> Result<()> {
+    state
+        .map_sessions
+        .set_read(&device_address, &message_path, read)
+        .await
+}
+
+#[tauri::command]
+pub async fn map_push_message<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, BluetoothManager>,
+    device_address: String,
+    recipient: String,
+    text: String,
+) -> Result<()> {
+    info!("Sending SMS to {} through {}", recipient, device_address);
+    state
+        .map_sessions
+        .push_message(&app, &device_address, &recipient, &text)
+        .await
 }
 
 #[tauri::command]
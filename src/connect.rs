@@ -0,0 +1,232 @@
+use futures::channel::oneshot;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+use tracing::{info, warn};
+use zbus::{Connection, Proxy};
+
+use crate::desktop::{fetch_device_info, BluetoothManager};
+use crate::events::{emit_change, BluetoothEvent};
+use crate::models::{ConnectOptions, DeviceInfo};
+use crate::object_cache::ObjectCache;
+use crate::{Error, Result};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(15);
+const DEFAULT_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Connection attempts in flight, keyed by the id the UI cancels them with.
+///
+/// The entry stays until the attempt returns; `cancel` only takes its sender.
+#[derive(Default)]
+pub struct ConnectAttempts {
+    pending: Mutex<HashMap<String, Option<oneshot::Sender<()>>>>,
+}
+
+struct AttemptGuard<'a> {
+    attempts: &'a ConnectAttempts,
+    id: String,
+}
+
+impl Drop for AttemptGuard<'_> {
+    fn drop(&mut self) {
+        self.attempts.pending.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl ConnectAttempts {
+    fn begin(&self, id: &str) -> Result<(AttemptGuard<'_>, oneshot::Receiver<()>)> {
+        let mut pending = self.pending.lock().unwrap();
+        if pending.contains_key(id) {
+            return Err(Error::CommandError(format!(
+                "Connection attempt {} already in progress",
+                id
+            )));
+        }
+        let (tx, rx) = oneshot::channel();
+        pending.insert(id.to_string(), Some(tx));
+        Ok((
+            AttemptGuard {
+                attempts: self,
+                id: id.to_string(),
+            },
+            rx,
+        ))
+    }
+
+    pub fn cancel(&self, id: &str) -> Result<()> {
+        let tx = self
+            .pending
+            .lock()
+            .unwrap()
+            .get_mut(id)
+            .and_then(Option::take)
+            .ok_or_else(|| Error::NotFound(format!("No connection attempt {}", id)))?;
+        let _ = tx.send(());
+        Ok(())
+    }
+}
+
+fn emit_progress<R: Runtime>(
+    app: &AppHandle<R>,
+    id: &str,
+    device_path: &str,
+    state: &str,
+    attempt: u32,
+    attempts: u32,
+    error: Option<String>,
+) {
+    emit_change(app, BluetoothEvent::other("connect-progress", serde_json::json!({
+        "id": id,
+        "device": device_path,
+        "state": state,
+        "attempt": attempt,
+        "attempts": attempts,
+        "error": error,
+    })));
+}
+
+/// Maps a `Device1.Connect` failure to an error and whether another attempt can help.
+fn classify(msg: String) -> (Error, bool) {
+    if msg.contains("NotReady") {
+        (Error::ConnectionFailed(format!("Adapter not ready: {}", msg)), false)
+    } else if msg.contains("DoesNotExist") || msg.contains("UnknownObject") {
+        (Error::NotFound(msg), false)
+    } else if msg.contains("AuthenticationFailed")
+        || msg.contains("AuthenticationRejected")
+        || msg.contains("AuthenticationCanceled")
+    {
+        (Error::PairingFailed(msg), false)
+    } else if msg.contains("profile-unavailable") || msg.contains("NotSupported") {
+        (Error::ConnectionFailed(msg), false)
+    } else {
+        (Error::ConnectionFailed(msg), true)
+    }
+}
+
+/// Aborts a pending or established connection; `Disconnect` also cancels an ongoing `Connect`.
+async fn abort(conn: &Connection, device_path: &str) {
+    if let Ok(proxy) = Proxy::new(conn, "org.bluez", device_path, "org.bluez.Device1").await {
+        let _ = proxy.call_method("Disconnect", &()).await;
+    }
+}
+
+async fn try_connect(
+    conn: &Connection,
+    objects: &ObjectCache,
+    device_path: &str,
+    timeout: Duration,
+    wait_services_resolved: bool,
+) -> std::result::Result<DeviceInfo, (Error, bool)> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    let proxy = Proxy::new(conn, "org.bluez", device_path, "org.bluez.Device1")
+        .await
+        .map_err(|e| (e.into(), false))?;
+
+    match tokio::time::timeout_at(deadline, proxy.call_method("Connect", &())).await {
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) => {
+            let msg = e.to_string();
+            // Someone else is already connecting: wait for the outcome like our own call
+            let in_progress = msg.contains("InProgress")
+                || msg.contains("br-connection-busy")
+                || msg.contains("AlreadyConnected")
+                || msg.contains("already-connected");
+            if !in_progress {
+                return Err(classify(msg));
+            }
+        }
+        Err(_) => {
+            abort(conn, device_path).await;
+            return Err((
+                Error::Timeout(format!("{} did not connect within {:?}", device_path, timeout)),
+                true,
+            ));
+        }
+    }
+
+    // A successful Connect isn't proof: links drop right away when the remote refuses.
+    // The cache follows PropertiesChanged, so polling it costs no D-Bus round trip
+    loop {
+        let device = match objects.device(device_path) {
+            Some(device) => device,
+            None => fetch_device_info(conn, device_path.to_string())
+                .await
+                .map_err(|e| (e, false))?,
+        };
+        if device.connected && (!wait_services_resolved || device.services_resolved) {
+            return Ok(device);
+        }
+        if tokio::time::Instant::now() >= deadline {
+            let what = if device.connected {
+                "did not resolve services"
+            } else {
+                "did not stay connected"
+            };
+            abort(conn, device_path).await;
+            return Err((
+                Error::Timeout(format!("{} {} within {:?}", device_path, what, timeout)),
+                true,
+            ));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// `Device1.Connect` with a per-attempt timeout, retries with exponential backoff and
+/// cancellation through `ConnectAttempts::cancel`. Returns the device once it is connected
+/// (and, if asked, once its services are resolved).
+pub async fn connect_with_options<R: Runtime>(
+    app: &AppHandle<R>,
+    conn: &Connection,
+    attempts: &ConnectAttempts,
+    device_path: &str,
+    options: ConnectOptions,
+) -> Result<DeviceInfo> {
+    let id = options.id.clone().unwrap_or_else(|| device_path.to_string());
+    let (_guard, mut canceled) = attempts.begin(&id)?;
+
+    let timeout = options.timeout_ms.map(Duration::from_millis).unwrap_or(DEFAULT_TIMEOUT);
+    let total = options.retries.unwrap_or(0).saturating_add(1);
+    let wait_services_resolved = options.wait_services_resolved.unwrap_or(false);
+    let mut backoff = options.backoff_ms.map(Duration::from_millis).unwrap_or(DEFAULT_BACKOFF);
+
+    let manager = app.state::<BluetoothManager>();
+    let current = AtomicU32::new(0);
+    let run = async {
+        loop {
+            let attempt = current.fetch_add(1, Ordering::Relaxed) + 1;
+            emit_progress(app, &id, device_path, "connecting", attempt, total, None);
+            match try_connect(conn, &manager.objects, device_path, timeout, wait_services_resolved).await {
+                Ok(device) => return Ok(device),
+                Err((e, retryable)) if retryable && attempt < total => {
+                    warn!("Connect attempt {}/{} to {} failed: {}", attempt, total, device_path, e);
+                    emit_progress(app, &id, device_path, "retrying", attempt, total, Some(e.to_string()));
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+                Err((e, _)) => return Err(e),
+            }
+        }
+    };
+
+    let result = tokio::select! {
+        outcome = run => outcome,
+        Ok(()) = &mut canceled => {
+            info!("Connection attempt {} canceled", id);
+            abort(conn, device_path).await;
+            Err(Error::Canceled(format!("Connection to {} canceled", device_path)))
+        }
+    };
+
+    let attempt = current.load(Ordering::Relaxed);
+    match &result {
+        Ok(_) => emit_progress(app, &id, device_path, "connected", attempt, total, None),
+        Err(Error::Canceled(_)) => emit_progress(app, &id, device_path, "canceled", attempt, total, None),
+        Err(e) => emit_progress(app, &id, device_path, "failed", attempt, total, Some(e.to_string())),
+    }
+    result
+}
@@ -0,0 +1,470 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::Shutdown;
+use std::os::fd::{AsRawFd, OwnedFd};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{ready, Context, Poll};
+use tauri::{AppHandle, Runtime};
+use tokio::io::unix::AsyncFd;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf};
+use tokio::sync::mpsc;
+use tracing::{error, info};
+use zbus::{
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value as ZbusValue},
+    Connection, DBusError, Proxy,
+};
+
+use crate::desktop::{object_remover, ObjectRemover};
+use crate::device_lookup::address_from_path;
+use crate::events::{emit_change, BluetoothEvent};
+use crate::models::{ProfileConnection, ProfileOptions};
+use crate::profiles::profile_uuid;
+use crate::{Error, Result};
+
+pub const PROFILE_BASE_PATH: &str = "/org/vasak/bluetooth/profile";
+
+const READ_BUFFER_SIZE: usize = 4096;
+
+/// The RFCOMM/L2CAP socket BlueZ passed in, registered with the Tokio reactor.
+type Socket = AsyncFd<OwnedFd>;
+
+/// An RFCOMM/L2CAP connection handed over by BlueZ through `Profile1.NewConnection`.
+///
+/// Reads and writes go straight to the socket; dropping the stream closes the connection.
+pub struct ProfileStream {
+    pub connection: ProfileConnection,
+    socket: Arc<Socket>,
+}
+
+impl ProfileStream {
+    /// Closes both directions; the remote (and any reader) sees EOF.
+    pub fn close(&self) -> io::Result<()> {
+        shutdown_socket(&self.socket, Shutdown::Both)
+    }
+
+    /// Second handle on the same socket, e.g. to close it after `tokio::io::split`.
+    pub(crate) fn share(&self) -> ProfileStream {
+        ProfileStream {
+            connection: self.connection.clone(),
+            socket: self.socket.clone(),
+        }
+    }
+}
+
+impl AsyncRead for ProfileStream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        loop {
+            let mut guard = ready!(self.socket.poll_read_ready(cx))?;
+            let unfilled = buf.initialize_unfilled();
+            match guard.try_io(|socket| read_fd(socket.get_ref(), unfilled)) {
+                Ok(Ok(n)) => {
+                    buf.advance(n);
+                    return Poll::Ready(Ok(()));
+                }
+                Ok(Err(e)) => return Poll::Ready(Err(e)),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+}
+
+impl AsyncWrite for ProfileStream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        loop {
+            let mut guard = ready!(self.socket.poll_write_ready(cx))?;
+            match guard.try_io(|socket| write_fd(socket.get_ref(), buf)) {
+                Ok(result) => return Poll::Ready(result),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(shutdown_socket(&self.socket, Shutdown::Write))
+    }
+}
+
+fn cvt(ret: libc::ssize_t) -> io::Result<usize> {
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret as usize)
+    }
+}
+
+fn read_fd(fd: &OwnedFd, buf: &mut [u8]) -> io::Result<usize> {
+    // SAFETY: the descriptor is open for as long as `fd` is borrowed and `buf` is writable
+    cvt(unsafe { libc::read(fd.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len()) })
+}
+
+fn write_fd(fd: &OwnedFd, buf: &[u8]) -> io::Result<usize> {
+    // SAFETY: the descriptor is open for as long as `fd` is borrowed and `buf` is readable
+    cvt(unsafe { libc::write(fd.as_raw_fd(), buf.as_ptr().cast(), buf.len()) })
+}
+
+fn shutdown_socket(socket: &Socket, how: Shutdown) -> io::Result<()> {
+    let how = match how {
+        Shutdown::Read => libc::SHUT_RD,
+        Shutdown::Write => libc::SHUT_WR,
+        Shutdown::Both => libc::SHUT_RDWR,
+    };
+    // SAFETY: plain syscall on a descriptor `socket` keeps open
+    if unsafe { libc::shutdown(socket.get_ref().as_raw_fd(), how) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+async fn write_all(socket: &Socket, mut data: &[u8]) -> io::Result<()> {
+    while !data.is_empty() {
+        let mut guard = socket.writable().await?;
+        match guard.try_io(|socket| write_fd(socket.get_ref(), data)) {
+            Ok(Ok(0)) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(Ok(n)) => data = &data[n..],
+            Ok(Err(e)) => return Err(e),
+            Err(_would_block) => continue,
+        }
+    }
+    Ok(())
+}
+
+/// BlueZ hands over RFCOMM/L2CAP sockets, so they are driven through the raw descriptor
+/// rather than posing as Unix sockets.
+///
+/// Must run inside the Tokio runtime: the socket gets registered with its reactor.
+fn into_async_socket(fd: OwnedFd) -> io::Result<Socket> {
+    // SAFETY: fcntl on a descriptor we own
+    let flags = unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_GETFL) };
+    if flags < 0 || unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_SETFL, flags | libc::O_NONBLOCK) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    AsyncFd::new(fd)
+}
+
+struct ConnectionEntry {
+    info: ProfileConnection,
+    socket: Weak<Socket>,
+    write_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+type Connections = Arc<Mutex<HashMap<String, ConnectionEntry>>>;
+
+/// Where new connections go: `profile-*` events for the frontend, or a channel of
+/// [`ProfileStream`]s for Rust consumers.
+#[derive(Clone)]
+enum Sink {
+    Events,
+    Streams(mpsc::UnboundedSender<ProfileStream>),
+}
+
+#[derive(DBusError, Debug)]
+#[zbus(prefix = "org.bluez.Error")]
+enum ProfileError {
+    #[zbus(error)]
+    ZBus(zbus::Error),
+    Rejected(String),
+    Failed(String),
+}
+
+struct CustomProfile<R: Runtime> {
+    app: AppHandle<R>,
+    uuid: String,
+    sink: Sink,
+    connections: Connections,
+    next_id: Arc<AtomicU64>,
+}
+
+fn emit<R: Runtime>(app: &AppHandle<R>, change_type: &str, data: serde_json::Value) {
+    emit_change(app, BluetoothEvent::other(change_type, data));
+}
+
+/// Forwards incoming bytes as `profile-data` until the socket closes.
+fn spawn_reader<R: Runtime>(app: AppHandle<R>, connections: Connections, stream: ProfileStream) {
+    tauri::async_runtime::spawn(async move {
+        let mut stream = stream;
+        let id = stream.connection.id.clone();
+        let mut buf = vec![0u8; READ_BUFFER_SIZE];
+        let reason = loop {
+            match stream.read(&mut buf).await {
+                Ok(0) => break "closed".to_string(),
+                Ok(n) => emit(&app, "profile-data", serde_json::json!({ "id": id, "data": &buf[..n] })),
+                Err(e) => break e.to_string(),
+            }
+        };
+
+        connections.lock().unwrap().remove(&id);
+        emit(&app, "profile-disconnected", serde_json::json!({
+            "id": id,
+            "device": stream.connection.device,
+            "reason": reason,
+        }));
+    });
+}
+
+#[zbus::interface(name = "org.bluez.Profile1")]
+impl<R: Runtime> CustomProfile<R> {
+    async fn release(&self) {
+        info!("Profile {} released by BlueZ", self.uuid);
+        emit(&self.app, "profile-released", serde_json::json!({ "uuid": self.uuid }));
+    }
+
+    async fn new_connection(
+        &self,
+        device: OwnedObjectPath,
+        fd: zbus::zvariant::OwnedFd,
+        _fd_properties: HashMap<String, OwnedValue>,
+    ) -> std::result::Result<(), ProfileError> {
+        let fd: OwnedFd = fd.into();
+        let socket = tauri::async_runtime::spawn(async move { into_async_socket(fd) })
+            .await
+            .map_err(|e| ProfileError::Failed(e.to_string()))?
+            .map_err(|e| ProfileError::Failed(e.to_string()))?;
+
+        let id = format!("conn{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let info = ProfileConnection {
+            id: id.clone(),
+            uuid: self.uuid.clone(),
+            device: device.to_string(),
+            address: address_from_path(device.as_str()).unwrap_or_default(),
+        };
+        info!("New {} connection {} from {}", self.uuid, id, info.device);
+
+        let stream = ProfileStream {
+            connection: info.clone(),
+            socket: Arc::new(socket),
+        };
+        {
+            let mut connections = self.connections.lock().unwrap();
+            connections.retain(|_, entry| entry.socket.strong_count() > 0);
+            connections.insert(id.clone(), ConnectionEntry {
+                info: info.clone(),
+                socket: Arc::downgrade(&stream.socket),
+                write_lock: Arc::new(tokio::sync::Mutex::new(())),
+            });
+        }
+        emit(&self.app, "profile-connected", serde_json::to_value(&info).unwrap_or_default());
+
+        match &self.sink {
+            Sink::Events => spawn_reader(self.app.clone(), self.connections.clone(), stream),
+            Sink::Streams(tx) => {
+                if let Err(mpsc::error::SendError(stream)) = tx.send(stream) {
+                    let _ = stream.close();
+                    self.connections.lock().unwrap().remove(&id);
+                    return Err(ProfileError::Rejected("No consumer for this profile".to_string()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn request_disconnection(&self, device: OwnedObjectPath) {
+        let sockets: Vec<Arc<Socket>> = {
+            let mut connections = self.connections.lock().unwrap();
+            let ids: Vec<String> = connections
+                .iter()
+                .filter(|(_, entry)| entry.info.device == device.as_str())
+                .map(|(id, _)| id.clone())
+                .collect();
+            ids.iter()
+                .filter_map(|id| connections.remove(id))
+                .filter_map(|entry| entry.socket.upgrade())
+                .collect()
+        };
+        for socket in sockets {
+            let _ = shutdown_socket(&socket, Shutdown::Both);
+        }
+    }
+}
+
+/// Custom profiles registered through `ProfileManager1` and their open connections.
+#[derive(Default)]
+pub struct CustomProfiles {
+    registered: Mutex<HashMap<String, (String, ObjectRemover)>>, // uuid -> object path
+    connections: Connections,
+    next_id: Arc<AtomicU64>,
+}
+
+fn profile_path(uuid: &str) -> String {
+    format!("{}/{}", PROFILE_BASE_PATH, uuid.replace('-', "_"))
+}
+
+fn profile_options(options: &ProfileOptions) -> HashMap<&'static str, ZbusValue<'_>> {
+    let mut dict: HashMap<&str, ZbusValue> = HashMap::new();
+    if let Some(name) = &options.name {
+        dict.insert("Name", ZbusValue::from(name.as_str()));
+    }
+    if let Some(role) = &options.role {
+        dict.insert("Role", ZbusValue::from(role.as_str()));
+    }
+    if let Some(channel) = options.channel {
+        dict.insert("Channel", ZbusValue::from(channel));
+    }
+    if let Some(psm) = options.psm {
+        dict.insert("PSM", ZbusValue::from(psm));
+    }
+    if let Some(value) = options.require_authentication {
+        dict.insert("RequireAuthentication", ZbusValue::from(value));
+    }
+    if let Some(value) = options.require_authorization {
+        dict.insert("RequireAuthorization", ZbusValue::from(value));
+    }
+    if let Some(value) = options.auto_connect {
+        dict.insert("AutoConnect", ZbusValue::from(value));
+    }
+    if let Some(record) = &options.service_record {
+        dict.insert("ServiceRecord", ZbusValue::from(record.as_str()));
+    }
+    dict
+}
+
+impl CustomProfiles {
+    async fn register_with_sink<R: Runtime>(
+        &self,
+        app: AppHandle<R>,
+        conn: &Connection,
+        options: ProfileOptions,
+        sink: Sink,
+    ) -> Result<String> {
+        if let Some(role) = options.role.as_deref() {
+            if role != "client" && role != "server" {
+                return Err(Error::CommandError(format!(
+                    "Unknown profile role: {} (expected \"client\" or \"server\")",
+                    role
+                )));
+            }
+        }
+        let uuid = profile_uuid(&options.uuid)?;
+        if self.registered.lock().unwrap().contains_key(&uuid) {
+            return Err(Error::CommandError(format!("Profile {} is already registered", uuid)));
+        }
+
+        let path = profile_path(&uuid);
+        let profile = CustomProfile {
+            app,
+            uuid: uuid.clone(),
+            sink,
+            connections: self.connections.clone(),
+            next_id: self.next_id.clone(),
+        };
+        conn.object_server().at(path.as_str(), profile).await?;
+
+        let proxy = Proxy::new(conn, "org.bluez", "/org/bluez", "org.bluez.ProfileManager1").await?;
+        if let Err(e) = proxy
+            .call_method(
+                "RegisterProfile",
+                &(ObjectPath::try_from(path.as_str())?, uuid.as_str(), profile_options(&options)),
+            )
+            .await
+        {
+            error!("Error registering profile {}: {}", uuid, e);
+            let _ = conn.object_server().remove::<CustomProfile<R>, _>(path.as_str()).await;
+            return Err(e.into());
+        }
+
+        info!("Profile {} registered at {}", uuid, path);
+        self.registered
+            .lock()
+            .unwrap()
+            .insert(uuid, (path.clone(), object_remover::<CustomProfile<R>>()));
+        Ok(path)
+    }
+
+    /// Registers a profile whose connections are surfaced as `profile-*` events.
+    pub async fn register<R: Runtime>(
+        &self,
+        app: AppHandle<R>,
+        conn: &Connection,
+        options: ProfileOptions,
+    ) -> Result<String> {
+        self.register_with_sink(app, conn, options, Sink::Events).await
+    }
+
+    /// Registers a profile and hands every new connection to the returned receiver.
+    pub async fn register_streams<R: Runtime>(
+        &self,
+        app: AppHandle<R>,
+        conn: &Connection,
+        options: ProfileOptions,
+    ) -> Result<mpsc::UnboundedReceiver<ProfileStream>> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.register_with_sink(app, conn, options, Sink::Streams(tx)).await?;
+        Ok(rx)
+    }
+
+    pub async fn unregister(&self, conn: &Connection, uuid: &str) -> Result<()> {
+        let uuid = profile_uuid(uuid)?;
+        let Some((path, remove)) = self.registered.lock().unwrap().remove(&uuid) else {
+            return Err(Error::NotFound(format!("Profile {} is not registered", uuid)));
+        };
+
+        let proxy = Proxy::new(conn, "org.bluez", "/org/bluez", "org.bluez.ProfileManager1").await?;
+        match proxy
+            .call_method("UnregisterProfile", &(ObjectPath::try_from(path.as_str())?,))
+            .await
+        {
+            Ok(_) => {}
+            Err(e) if e.to_string().contains("DoesNotExist") => {}
+            Err(e) => error!("Error unregistering profile {}: {}", uuid, e),
+        }
+        remove(conn.clone(), path).await?;
+
+        let ids: Vec<String> = self
+            .connections
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|entry| entry.info.uuid == uuid)
+            .map(|entry| entry.info.id.clone())
+            .collect();
+        for id in ids {
+            let _ = self.close(&id);
+        }
+        Ok(())
+    }
+
+    pub fn list_connections(&self) -> Vec<ProfileConnection> {
+        let mut connections = self.connections.lock().unwrap();
+        connections.retain(|_, entry| entry.socket.strong_count() > 0);
+        connections.values().map(|entry| entry.info.clone()).collect()
+    }
+
+    /// Writes the whole buffer, waiting while the socket is full.
+    pub async fn write(&self, id: &str, data: &[u8]) -> Result<()> {
+        let (socket, write_lock) = {
+            let connections = self.connections.lock().unwrap();
+            let entry = connections
+                .get(id)
+                .ok_or_else(|| Error::NotFound(format!("Profile connection not found: {}", id)))?;
+            let socket = entry
+                .socket
+                .upgrade()
+                .ok_or_else(|| Error::NotFound(format!("Profile connection closed: {}", id)))?;
+            (socket, entry.write_lock.clone())
+        };
+
+        let _guard = write_lock.lock().await;
+        write_all(&socket, data)
+            .await
+            .map_err(|e| Error::CommandError(format!("Error writing to {}: {}", id, e)))
+    }
+
+    pub fn close(&self, id: &str) -> Result<()> {
+        let entry = self
+            .connections
+            .lock()
+            .unwrap()
+            .remove(id)
+            .ok_or_else(|| Error::NotFound(format!("Profile connection not found: {}", id)))?;
+        if let Some(socket) = entry.socket.upgrade() {
+            shutdown_socket(&socket, Shutdown::Both)
+                .map_err(|e| Error::CommandError(format!("Error closing {}: {}", id, e)))?;
+        }
+        Ok(())
+    }
+}
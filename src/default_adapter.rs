@@ -0,0 +1,143 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime};
+use tracing::{error, info};
+use zbus::Connection;
+
+use crate::desktop::fetch_adapters;
+use crate::events::{emit_change, BluetoothEvent};
+use crate::models::{AdapterInfo};
+use crate::{Error, Result};
+
+/// Accepted in place of an adapter path by every command that takes one.
+pub const DEFAULT_ADAPTER: &str = "default";
+
+const STORE_FILE: &str = "bluetooth-default-adapter.json";
+
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+struct StoredChoice {
+    address: Option<String>,
+}
+
+/// Default adapter policy: the adapter the user picked (remembered by address, since
+/// hciN changes across reboots and replugs) when present, otherwise the first powered one.
+pub struct DefaultAdapter {
+    conn: Connection,
+    preferred: Mutex<Option<String>>,
+    current: Mutex<Option<String>>,
+    store: Mutex<Option<PathBuf>>,
+}
+
+fn pick(adapters: &mut [AdapterInfo], preferred: Option<&str>) -> Option<AdapterInfo> {
+    adapters.sort_by(|a, b| a.path.cmp(&b.path));
+    preferred
+        .and_then(|address| adapters.iter().find(|a| a.address.eq_ignore_ascii_case(address)))
+        .or_else(|| adapters.iter().find(|a| a.powered))
+        .or_else(|| adapters.first())
+        .cloned()
+}
+
+impl DefaultAdapter {
+    pub fn new(conn: Connection) -> Self {
+        Self {
+            conn,
+            preferred: Mutex::new(None),
+            current: Mutex::new(None),
+            store: Mutex::new(None),
+        }
+    }
+
+    /// Loads the persisted choice from the app config dir.
+    pub fn load<R: Runtime>(&self, app: &AppHandle<R>) {
+        let Ok(dir) = app.path().app_config_dir() else {
+            return;
+        };
+        let path = dir.join(STORE_FILE);
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            match serde_json::from_str::<StoredChoice>(&content) {
+                Ok(choice) => *self.preferred.lock().unwrap() = choice.address,
+                Err(e) => error!("Invalid {}: {}", path.display(), e),
+            }
+        }
+        *self.store.lock().unwrap() = Some(path);
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = self.store.lock().unwrap().clone() else {
+            return Ok(());
+        };
+        let choice = StoredChoice {
+            address: self.preferred.lock().unwrap().clone(),
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| Error::CommandError(format!("Error creating {}: {}", dir.display(), e)))?;
+        }
+        let content = serde_json::to_string_pretty(&choice).unwrap_or_default();
+        std::fs::write(&path, content)
+            .map_err(|e| Error::CommandError(format!("Error writing {}: {}", path.display(), e)))
+    }
+
+    /// The adapter the policy currently picks, if any adapter exists.
+    pub async fn get(&self) -> Result<Option<AdapterInfo>> {
+        let mut adapters = fetch_adapters(&self.conn).await?;
+        let preferred = self.preferred.lock().unwrap().clone();
+        Ok(pick(&mut adapters, preferred.as_deref()))
+    }
+
+    /// Maps `"default"` to the current default adapter path; any other value is returned as is.
+    pub async fn resolve(&self, adapter_path: String) -> Result<String> {
+        if adapter_path != DEFAULT_ADAPTER {
+            return Ok(adapter_path);
+        }
+        self.get()
+            .await?
+            .map(|adapter| adapter.path)
+            .ok_or_else(|| Error::NotFound("No Bluetooth adapter available".to_string()))
+    }
+
+    /// Remembers `adapter` (a path or an address) as the user's choice; `None` goes back
+    /// to the first powered adapter.
+    pub async fn set<R: Runtime>(&self, app: &AppHandle<R>, adapter: Option<String>) -> Result<()> {
+        let address = match adapter {
+            Some(adapter) if adapter.starts_with('/') => {
+                let adapters = fetch_adapters(&self.conn).await?;
+                let found = adapters
+                    .into_iter()
+                    .find(|a| a.path == adapter)
+                    .ok_or_else(|| Error::NotFound(format!("Adapter not found: {}", adapter)))?;
+                Some(found.address)
+            }
+            Some(address) => Some(address.to_uppercase()),
+            None => None,
+        };
+
+        info!("Default adapter preference: {:?}", address);
+        *self.preferred.lock().unwrap() = address;
+        self.save()?;
+        self.refresh(app).await;
+        Ok(())
+    }
+
+    /// Recomputes the default and emits `default-adapter-changed` when it moved.
+    pub async fn refresh<R: Runtime>(&self, app: &AppHandle<R>) {
+        let adapter = match self.get().await {
+            Ok(adapter) => adapter,
+            Err(e) => {
+                error!("Error computing default adapter: {}", e);
+                return;
+            }
+        };
+
+        let path = adapter.as_ref().map(|a| a.path.clone());
+        {
+            let mut current = self.current.lock().unwrap();
+            if *current == path {
+                return;
+            }
+            *current = path;
+        }
+
+        emit_change(app, BluetoothEvent::DefaultAdapterChanged(adapter));
+    }
+}
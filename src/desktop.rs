@@ -1,8 +1,9 @@
 use futures::StreamExt;
 use std::sync::Mutex;
-use tauri::{plugin::PluginApi, AppHandle, Emitter, Manager, Runtime};
+use tauri::{ipc::Channel, AppHandle, Manager, Runtime};
+use tracing::{error, info};
 use zbus::{
-    zvariant::{ObjectPath, OwnedValue, Value as ZbusValue},
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value as ZbusValue},
     Connection, MessageStream, MessageType, Proxy,
 };
 use std::collections::HashMap;
@@ -19,6 +20,40 @@ fn get_prop_vec(props: &HashMap<String, OwnedValue>, key: &str) -> Vec<String> {
     }).unwrap_or_default()
 }
 
+/// Converts a D-Bus value to JSON; dict keys become strings (ManufacturerData is keyed by
+/// company id).
+pub(crate) fn value_to_json(value: &ZbusValue<'_>) -> serde_json::Value {
+    match value {
+        ZbusValue::U8(v) => serde_json::json!(v),
+        ZbusValue::Bool(v) => serde_json::json!(v),
+        ZbusValue::I16(v) => serde_json::json!(v),
+        ZbusValue::U16(v) => serde_json::json!(v),
+        ZbusValue::I32(v) => serde_json::json!(v),
+        ZbusValue::U32(v) => serde_json::json!(v),
+        ZbusValue::I64(v) => serde_json::json!(v),
+        ZbusValue::U64(v) => serde_json::json!(v),
+        ZbusValue::F64(v) => serde_json::json!(v),
+        ZbusValue::Str(v) => serde_json::json!(v.as_str()),
+        ZbusValue::Signature(v) => serde_json::json!(v.as_str()),
+        ZbusValue::ObjectPath(v) => serde_json::json!(v.as_str()),
+        ZbusValue::Value(v) => value_to_json(v),
+        ZbusValue::Array(arr) => serde_json::Value::Array(arr.iter().map(value_to_json).collect()),
+        ZbusValue::Dict(dict) => serde_json::Value::Object(
+            dict.iter()
+                .map(|(k, v)| {
+                    let key = match value_to_json(k) {
+                        serde_json::Value::String(s) => s,
+                        other => other.to_string(),
+                    };
+                    (key, value_to_json(v))
+                })
+                .collect(),
+        ),
+        ZbusValue::Structure(s) => serde_json::Value::Array(s.fields().iter().map(value_to_json).collect()),
+        _ => serde_json::Value::Null,
+    }
+}
+
 macro_rules! get_prop {
     ($props:expr, $key:expr, $ty:ty) => {
         $props.get($key).and_then(|v| <$ty>::try_from(&**v).ok())
@@ -28,29 +63,117 @@ macro_rules! get_prop {
     };
 }
 
-use crate::commands::{get_adapter_state, get_device_info};
 use crate::models::*;
+use crate::adv_monitor::AdvertisementMonitors;
+use crate::agent::PairingAgentState;
+use crate::connect::ConnectAttempts;
+use crate::custom_profiles::{CustomProfiles, ProfileStream};
+use crate::default_adapter::DefaultAdapter;
+use crate::device_history::{DeviceHistoryStore, Sighting};
+use crate::device_lookup::{address_from_path, device_id};
+use crate::events::{emit_change, BluetoothEvent, EventBus};
+use crate::spp::{SppClients, SppStream};
+use crate::subscriptions::Subscriptions;
+use crate::hid::{hid_info, HidSetups};
+use crate::obex::ObexTransfers;
+use crate::obex_agent::ObexAgentState;
+use crate::object_cache::ObjectCache;
+use crate::presence::{Observation, PresenceEngine};
+use crate::reconnect::AutoReconnect;
+use crate::scan::ScanSessions;
+use crate::throttle::EventThrottle;
+use crate::map::MapSessions;
+use crate::obex_ftp::FtpSessions;
+use crate::profiles::resolve_profile_uuid;
 use crate::Result as CrateResult;
 
 pub struct BluetoothManager {
     pub conn: Connection,
     pub initialized: Mutex<bool>,
+    pub obex_agent: ObexAgentState,
+    pub obex_transfers: ObexTransfers,
+    pub ftp_sessions: FtpSessions,
+    pub map_sessions: MapSessions,
+    pub pairing_agent: PairingAgentState,
+    pub hid_setups: HidSetups,
+    pub connect_attempts: ConnectAttempts,
+    pub auto_reconnect: AutoReconnect,
+    pub custom_profiles: CustomProfiles,
+    pub spp: SppClients,
+    pub default_adapter: DefaultAdapter,
+    pub device_history: DeviceHistoryStore,
+    pub presence: PresenceEngine,
+    pub adv_monitors: AdvertisementMonitors,
+    pub throttle: EventThrottle,
+    pub objects: ObjectCache,
+    pub subscriptions: Subscriptions,
+    pub events: EventBus,
+    pub scans: ScanSessions,
+    pub config: PluginConfig,
 }
 
-pub async fn init<R: Runtime>(app: AppHandle<R>, _api: PluginApi<R, ()>) -> CrateResult<()> {
+pub async fn init<R: Runtime>(app: AppHandle<R>, config: PluginConfig) -> CrateResult<()> {
     let conn = Connection::system().await?;
 
     let manager = BluetoothManager {
         conn: conn.clone(),
         initialized: Mutex::new(false),
+        obex_agent: ObexAgentState::default(),
+        obex_transfers: ObexTransfers::default(),
+        ftp_sessions: FtpSessions::default(),
+        map_sessions: MapSessions::default(),
+        pairing_agent: PairingAgentState::default(),
+        hid_setups: HidSetups::default(),
+        connect_attempts: ConnectAttempts::default(),
+        auto_reconnect: AutoReconnect::default(),
+        custom_profiles: CustomProfiles::default(),
+        spp: SppClients::default(),
+        default_adapter: DefaultAdapter::new(conn.clone()),
+        device_history: DeviceHistoryStore::default(),
+        presence: PresenceEngine::default(),
+        adv_monitors: AdvertisementMonitors::default(),
+        throttle: EventThrottle::new(config.throttle.clone()),
+        objects: ObjectCache::default(),
+        subscriptions: Subscriptions::default(),
+        events: EventBus::default(),
+        scans: ScanSessions::default(),
+        config,
     };
+    manager.default_adapter.load(&app);
+    manager.auto_reconnect.load(&app);
+    manager.device_history.load(&app);
 
     app.manage(manager);
+    app.state::<BluetoothManager>().default_adapter.refresh(&app).await;
 
     // Suscribirse explícitamente a las señales antes de iniciar el listener
     setup_dbus_subscriptions(&conn).await?;
+    if let Err(e) = app.state::<BluetoothManager>().objects.load(&conn).await {
+        eprintln!("[bluetooth-plugin] Failed to load BlueZ objects: {:?}", e);
+    }
+    if app.state::<BluetoothManager>().config.auto_power_on {
+        // Off the setup path: a slow adapter must not hold up plugin initialization
+        let app = app.clone();
+        let conn = conn.clone();
+        tauri::async_runtime::spawn(async move {
+            match fetch_adapters(&conn).await {
+                Ok(adapters) => {
+                    for adapter in adapters {
+                        auto_power_on(&app, &adapter).await;
+                    }
+                }
+                Err(e) => error!("Error listing adapters to power on: {}", e),
+            }
+        });
+    }
 
-    tauri::async_runtime::spawn(run_signal_listener(conn, app));
+    crate::rfkill::spawn_watcher(app.clone());
+    DeviceHistoryStore::spawn_flusher(app.clone());
+    PresenceEngine::spawn_ticker(app.clone());
+    EventThrottle::spawn_flusher(app.clone());
+    ScanSessions::spawn_ticker(app.clone());
+    tauri::async_runtime::spawn(run_signal_listener(conn, app.clone()));
+    app.state::<BluetoothManager>().auto_reconnect.trigger_all(&app, "startup");
 
     Ok(())
 }
@@ -87,6 +210,7 @@ async fn setup_dbus_subscriptions(conn: &Connection) -> CrateResult<()> {
         "type='signal',sender='org.bluez',interface='org.freedesktop.DBus.Properties'",
         "type='signal',sender='org.bluez',interface='org.bluez.Adapter1'",
         "type='signal',sender='org.bluez',interface='org.bluez.Device1'",
+        "type='signal',sender='org.bluez',interface='org.bluez.Network1'",
     ];
 
     for rule in rules {
@@ -99,7 +223,7 @@ async fn setup_dbus_subscriptions(conn: &Connection) -> CrateResult<()> {
     Ok(())
 }
 
-fn helper_adapter_info_from_props(
+pub(crate) fn helper_adapter_info_from_props(
     path: String,
     props: &HashMap<String, OwnedValue>,
 ) -> AdapterInfo {
@@ -120,39 +244,115 @@ fn helper_adapter_info_from_props(
     }
 }
 
-fn helper_device_info_from_props(path: String, props: &HashMap<String, OwnedValue>) -> DeviceInfo {
+pub(crate) fn helper_device_info_from_props(
+    path: String,
+    props: &HashMap<String, OwnedValue>,
+    input_props: Option<&HashMap<String, OwnedValue>>,
+) -> DeviceInfo {
+    let uuids = get_prop_vec(props, "UUIDs");
+    let paired = get_prop!(props, "Paired", bool, false);
+    let address = get_prop!(props, "Address", String, String::new());
     DeviceInfo {
+        id: device_id(&address),
         path,
-        address: get_prop!(props, "Address", String, String::new()),
+        address,
         name: get_prop!(props, "Name", String),
         alias: get_prop!(props, "Alias", String),
         class: get_prop!(props, "Class", u32),
         appearance: get_prop!(props, "Appearance", u16),
         icon: get_prop!(props, "Icon", String),
-        paired: get_prop!(props, "Paired", bool, false),
+        paired,
         trusted: get_prop!(props, "Trusted", bool, false),
         blocked: get_prop!(props, "Blocked", bool, false),
         legacy_pairing: get_prop!(props, "LegacyPairing", bool, false),
         rssi: get_prop!(props, "RSSI", i16),
         tx_power: get_prop!(props, "TxPower", i16),
         connected: get_prop!(props, "Connected", bool, false),
-        uuids: get_prop_vec(props, "UUIDs"),
+        hid: hid_info(&uuids, input_props),
+        uuids,
         adapter: props.get("Adapter")
             .and_then(|v| ObjectPath::try_from(&**v).ok())
             .map(|p: ObjectPath| p.to_string())
             .unwrap_or_default(),
         services_resolved: get_prop!(props, "ServicesResolved", bool, false),
+        history: None,
     }
 }
 
+/// `autoPowerOn`: turns on an adapter that showed up powered off.
+async fn auto_power_on<R: Runtime>(app: &AppHandle<R>, adapter: &AdapterInfo) {
+    let manager = app.state::<BluetoothManager>();
+    if !manager.config.auto_power_on || adapter.powered {
+        return;
+    }
+    info!("Powering on {}", adapter.path);
+    if let Err(e) = manager.set_adapter_powered(&adapter.path, true).await {
+        error!("Error powering on {}: {}", adapter.path, e);
+    }
+}
+
+fn normalize_network_role(role: &str) -> CrateResult<String> {
+    let role = role.trim().to_lowercase();
+    match role.as_str() {
+        "nap" | "panu" | "gn" => Ok(role),
+        _ if crate::profiles::is_full_uuid(&role) => Ok(role),
+        _ => Err(crate::Error::CommandError(format!(
+            "Unknown network role: {} (expected \"nap\", \"panu\" or \"gn\")",
+            role
+        ))),
+    }
+}
+
+/// `ObjectServer::remove` with the interface type fixed at registration, so removing an
+/// exported object later doesn't need to name its `Runtime`.
+pub type ObjectRemover = fn(Connection, String) -> futures::future::BoxFuture<'static, zbus::Result<bool>>;
+
+pub fn object_remover<I: zbus::object_server::Interface>() -> ObjectRemover {
+    |conn, path| Box::pin(async move { conn.object_server().remove::<I, _>(path.as_str()).await })
+}
+
+/// All adapters, straight from `GetManagedObjects`.
+pub async fn fetch_adapters(conn: &Connection) -> CrateResult<Vec<AdapterInfo>> {
+    let proxy = Proxy::new(conn, "org.bluez", "/", "org.freedesktop.DBus.ObjectManager").await?;
+    let reply = proxy.call_method("GetManagedObjects", &()).await?;
+    let objects: HashMap<OwnedObjectPath, HashMap<String, HashMap<String, OwnedValue>>> =
+        reply.body().deserialize()?;
+
+    let mut adapters: Vec<AdapterInfo> = objects
+        .iter()
+        .filter_map(|(path, interfaces)| {
+            interfaces
+                .get("org.bluez.Adapter1")
+                .map(|props| helper_adapter_info_from_props(path.to_string(), props))
+        })
+        .collect();
+    adapters.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(adapters)
+}
+
+/// `Device1` properties of a device, without the plugin's history.
+pub async fn fetch_device_info(conn: &Connection, device_path: String) -> CrateResult<DeviceInfo> {
+    let proxy = Proxy::new(conn, "org.bluez", device_path.as_str(), "org.freedesktop.DBus.Properties").await?;
+    let reply = proxy.call_method("GetAll", &("org.bluez.Device1",)).await?;
+    let props: HashMap<String, OwnedValue> = reply.body().deserialize()?;
+    let input = crate::hid::input_props(conn, &device_path).await;
+    Ok(helper_device_info_from_props(device_path, &props, input.as_ref()))
+}
+
+/// Device info from the object cache, fetched only when the cache doesn't know it yet.
+async fn device_snapshot<R: Runtime>(app: &AppHandle<R>, device_path: &str) -> CrateResult<DeviceInfo> {
+    let manager = app.state::<BluetoothManager>();
+    let mut device_info = match manager.objects.device(device_path) {
+        Some(device_info) => device_info,
+        None => fetch_device_info(&manager.conn, device_path.to_string()).await?,
+    };
+    manager.device_history.annotate(&mut device_info);
+    Ok(device_info)
+}
+
 async fn run_signal_listener<R: Runtime>(conn: Connection, app: AppHandle<R>) {
-    use std::time::{Duration, Instant};
     let mut stream = MessageStream::from(conn.clone());
 
-    // Throttling state
-    let mut device_last_update: HashMap<String, Instant> = HashMap::new();
-    const UPDATE_THROTTLE: Duration = Duration::from_millis(500);
-
     // Obtener el nombre único de org.bluez para comparación
     let mut bluez_unique_name: Option<String> = None;
     
@@ -205,33 +405,42 @@ async fn run_signal_listener<R: Runtime>(conn: Connection, app: AppHandle<R>) {
                             match msg.body().deserialize::<(ObjectPath<'_>, HashMap<String, HashMap<String, OwnedValue>>)>() {
                                 Ok((object_path, interfaces_and_properties)) => {
                                   let path_string = object_path.to_string();
+                                  app.state::<BluetoothManager>().objects.add(&path_string, &interfaces_and_properties);
                                   
                                   // Detectar cambios de adaptadores
                                   if let Some(adapter_props) = interfaces_and_properties.get("org.bluez.Adapter1") {
                                     let adapter_info = helper_adapter_info_from_props(path_string.clone(), adapter_props);
                                     
-                                    app.emit("bluetooth-change", BluetoothChange {
-                                        change_type: "adapter-added".to_string(),
-                                        data: serde_json::to_value(adapter_info).unwrap_or_default(),
-                                    }).unwrap_or_else(|e| eprintln!("[bluetooth-plugin] Failed to emit adapter-added: {}", e));
+                                    emit_change(&app, BluetoothEvent::AdapterAdded(adapter_info.clone()));
+                                    app.state::<BluetoothManager>().default_adapter.refresh(&app).await;
+                                    auto_power_on(&app, &adapter_info).await;
                                   }
                                   
                                   // Detectar cambios de dispositivos
                                   if let Some(device_props) = interfaces_and_properties.get("org.bluez.Device1") {
-                                    let device_info = helper_device_info_from_props(path_string.clone(), device_props);
+                                    let mut device_info = helper_device_info_from_props(
+                                        path_string.clone(),
+                                        device_props,
+                                        interfaces_and_properties.get("org.bluez.Input1"),
+                                    );
+                                    let manager = app.state::<BluetoothManager>();
+                                    manager.device_history.observe(&device_info.id, Sighting::from_device(&device_info));
+                                    manager.scans.on_device_changed(&manager, &path_string);
+                                    manager.device_history.annotate(&mut device_info);
+                                    manager.presence.observe(&app, &path_string, Observation {
+                                        rssi: device_info.rssi,
+                                        tx_power: device_info.tx_power,
+                                        connected: Some(device_info.connected),
+                                    });
                                     
-                                    app.emit("bluetooth-change", BluetoothChange {
-                                        change_type: "device-added".to_string(),
-                                        data: serde_json::to_value(device_info).unwrap_or_default(),
-                                    }).unwrap_or_else(|e| eprintln!("[bluetooth-plugin] Failed to emit device-added: {}", e));
+                                    emit_change(&app, BluetoothEvent::DeviceAdded(device_info));
                                   }
                                 }
                                 Err(e) => {
                                   eprintln!("[bluetooth-plugin] Error decoding InterfacesAdded body: {:?}", e);
-                                  app.emit("bluetooth-change", BluetoothChange {
-                                      change_type: "error".to_string(),
-                                      data: serde_json::json!({ "message": format!("Error decoding InterfacesAdded: {:?}", e) }),
-                                  }).unwrap_or_else(|err| eprintln!("[bluetooth-plugin] Failed to emit error: {}", err));
+                                  emit_change(&app, BluetoothEvent::Error {
+                                      message: format!("Error decoding InterfacesAdded: {:?}", e),
+                                  });
                                 }
                               }
                         }
@@ -239,28 +448,26 @@ async fn run_signal_listener<R: Runtime>(conn: Connection, app: AppHandle<R>) {
                             match msg.body().deserialize::<(ObjectPath<'_>, Vec<String>)>() {
                                 Ok((object_path, interfaces_removed)) => {
                                   let path_string = object_path.to_string();
+                                  // Taken before the cache forgets it, for paired-only subscribers
+                                  let was_paired = app.state::<BluetoothManager>().objects.device(&path_string).map(|d| d.paired).unwrap_or(false);
+                                  app.state::<BluetoothManager>().objects.remove(&path_string, &interfaces_removed);
                                   
                                   if interfaces_removed.contains(&"org.bluez.Adapter1".to_string()) {
-                                    app.emit("bluetooth-change", BluetoothChange {
-                                        change_type: "adapter-removed".to_string(),
-                                        data: serde_json::json!({ "path": path_string.clone() }),
-                                    }).unwrap_or_else(|e| eprintln!("[bluetooth-plugin] Failed to emit adapter-removed: {}", e));
+                                    emit_change(&app, BluetoothEvent::AdapterRemoved { path: path_string.clone() });
+                                    app.state::<BluetoothManager>().default_adapter.refresh(&app).await;
                                   }
                                   
                                   if interfaces_removed.contains(&"org.bluez.Device1".to_string()) {
-                                    
-                                    app.emit("bluetooth-change", BluetoothChange {
-                                        change_type: "device-removed".to_string(),
-                                        data: serde_json::json!({ "path": path_string }),
-                                    }).unwrap_or_else(|e| eprintln!("[bluetooth-plugin] Failed to emit device-removed: {}", e));
+                                    app.state::<BluetoothManager>().presence.remove(&app, &path_string);
+                                    app.state::<BluetoothManager>().scans.on_device_removed(&path_string);
+                                    emit_change(&app, BluetoothEvent::DeviceRemoved { path: path_string, paired: was_paired });
                                   }
                                 }
                                 Err(e) => {
                                   eprintln!("[bluetooth-plugin] Error decoding InterfacesRemoved body: {:?}", e);
-                                  app.emit("bluetooth-change", BluetoothChange {
-                                      change_type: "error".to_string(),
-                                      data: serde_json::json!({ "message": format!("Error decoding InterfacesRemoved: {:?}", e) }),
-                                  }).unwrap_or_else(|err| eprintln!("[bluetooth-plugin] Failed to emit error: {}", err));
+                                  emit_change(&app, BluetoothEvent::Error {
+                                      message: format!("Error decoding InterfacesRemoved: {:?}", e),
+                                  });
                                 }
                               }
                         }
@@ -268,107 +475,127 @@ async fn run_signal_listener<R: Runtime>(conn: Connection, app: AppHandle<R>) {
                             
                             if let Some(p_str) = path_opt_string {
                                 match msg.body().deserialize::<(String, HashMap<String, ZbusValue<'_>>, Vec<String>)>() {
-                                    Ok((changed_interface_name, changed_properties, _invalidated_properties)) => {
+                                    Ok((changed_interface_name, changed_properties, invalidated_properties)) => {
+                                        app.state::<BluetoothManager>().objects.update(
+                                            &p_str,
+                                            &changed_interface_name,
+                                            &changed_properties,
+                                            &invalidated_properties,
+                                        );
                                         if changed_interface_name == "org.bluez.Adapter1" {
-                                            match get_adapter_state(p_str.clone()).await {
-                                                Ok(adapter_info) => {
-                                                    app.emit("bluetooth-change", BluetoothChange {
-                                                        change_type: "adapter-property-changed".to_string(),
-                                                        data: serde_json::to_value(adapter_info).unwrap_or_default(),
-                                                    }).unwrap_or_else(|e| eprintln!("[bluetooth-plugin] Failed to emit adapter-property-changed: {}", e));
-                                                }
-                                                Err(e) => {
-                                                    eprintln!("[bluetooth-plugin] Error getting adapter state for {}: {:?}", p_str, e);
-                                                    app.emit("bluetooth-change", BluetoothChange {
-                                                        change_type: "error".to_string(),
-                                                        data: serde_json::json!({ "message": format!("Error getting adapter state: {:?}", e) }),
-                                                    }).unwrap_or_else(|err| eprintln!("[bluetooth-plugin] Failed to emit error: {}", err));
+                                            let manager = app.state::<BluetoothManager>();
+                                            let changed: serde_json::Map<String, serde_json::Value> = changed_properties
+                                                .iter()
+                                                .map(|(k, v)| (k.clone(), value_to_json(v)))
+                                                .collect();
+                                            emit_change(&app, BluetoothEvent::AdapterPropertyChanged {
+                                                path: p_str.clone(),
+                                                changed,
+                                                invalidated: invalidated_properties,
+                                                adapter: manager.objects.adapter(&p_str),
+                                            });
+                                            if let Some(powered) = changed_properties.get("Powered").and_then(|v| bool::try_from(v).ok()) {
+                                                manager.default_adapter.refresh(&app).await;
+                                                if powered {
+                                                    manager.auto_reconnect.on_adapter_powered(&app);
                                                 }
                                             }
                                         } 
+                                        else if changed_interface_name == "org.bluez.Network1" {
+                                            let mut data = serde_json::json!({ "path": p_str.clone() });
+                                            if let Some(connected) = changed_properties.get("Connected").and_then(|v| bool::try_from(v).ok()) {
+                                                data["connected"] = serde_json::json!(connected);
+                                            }
+                                            if let Some(interface) = changed_properties.get("Interface").and_then(|v| <&str>::try_from(v).ok()) {
+                                                data["interface"] = serde_json::json!(interface);
+                                            }
+                                            emit_change(&app, BluetoothEvent::other("device-network-changed", data));
+                                        }
                                         else if changed_interface_name == "org.bluez.Device1" {
-                                            // Throttling logic for device properties
-                                            let critical_keys = ["Connected", "Paired", "Trusted", "Blocked", "Name", "Alias"];
-                                            let is_critical = changed_properties.keys().any(|k| critical_keys.contains(&k.as_str()));
-
-                                            if !is_critical {
-                                                if let Some(last) = device_last_update.get(&p_str) {
-                                                    if last.elapsed() < UPDATE_THROTTLE {
-                                                        // Skip this update
-                                                        continue;
-                                                    }
+                                            // Every RSSI sample feeds the presence filter, throttled or not
+                                            app.state::<BluetoothManager>().presence.observe(&app, &p_str, Observation {
+                                                rssi: changed_properties.get("RSSI").and_then(|v| i16::try_from(v).ok()),
+                                                tx_power: changed_properties.get("TxPower").and_then(|v| i16::try_from(v).ok()),
+                                                connected: changed_properties.get("Connected").and_then(|v| bool::try_from(v).ok()),
+                                            });
+                                            if let Some(address) = address_from_path(&p_str) {
+                                                let manager = app.state::<BluetoothManager>();
+                                                match changed_properties.get("Connected").and_then(|v| bool::try_from(v).ok()) {
+                                                    Some(true) => manager.auto_reconnect.on_connected(&address),
+                                                    Some(false) => manager.auto_reconnect.on_disconnected(&app, &address),
+                                                    None if changed_properties.contains_key("RSSI") => manager.auto_reconnect.on_seen(&app, &p_str, &address),
+                                                    None => {}
                                                 }
-                                                device_last_update.insert(p_str.clone(), Instant::now());
                                             }
 
-                                            match get_device_info(p_str.clone()).await {
-                                                Ok(device_info) => {
-                                                    // println!("[bluetooth-plugin] Device property changed: {}", p_str);
-                                                    app.emit("bluetooth-change", BluetoothChange {
-                                                        change_type: "device-property-changed".to_string(),
-                                                        data: serde_json::to_value(device_info).unwrap_or_default(),
-                                                    }).unwrap_or_else(|e| eprintln!("[bluetooth-plugin] Failed to emit device-property-changed: {}", e));
-                                                }
-                                                Err(e) => {
-                                                    eprintln!("[bluetooth-plugin] Error getting device info for {}: {:?}", p_str, e);
-                                                    app.emit("bluetooth-change", BluetoothChange {
-                                                        change_type: "error".to_string(),
-                                                        data: serde_json::json!({ "message": format!("Error getting device info: {:?}", e) }),
-                                                    }).unwrap_or_else(|err| eprintln!("[bluetooth-plugin] Failed to emit error: {}", err));
-                                                }
+                                            let connected = changed_properties.get("Connected").and_then(|v| bool::try_from(v).ok()) == Some(true);
+                                            if let Some(address) = address_from_path(&p_str) {
+                                                app.state::<BluetoothManager>().device_history.observe(&address, Sighting {
+                                                    rssi: changed_properties.get("RSSI").and_then(|v| i16::try_from(v).ok()),
+                                                    name: changed_properties
+                                                        .get("Alias")
+                                                        .or_else(|| changed_properties.get("Name"))
+                                                        .and_then(|v| <&str>::try_from(v).ok())
+                                                        .map(|n| n.to_string()),
+                                                    connected,
+                                                    new_connection: connected,
+                                                });
                                             }
+
+                                            let manager = app.state::<BluetoothManager>();
+                                            manager.scans.on_device_changed(&manager, &p_str);
+
+                                            // Only the changed properties go out, rate limited per property
+                                            let changed = changed_properties
+                                                .iter()
+                                                .map(|(k, v)| (k.clone(), value_to_json(v)));
+                                            app.state::<BluetoothManager>().throttle.push(&app, &p_str, changed, invalidated_properties);
                                         }
                                     }
                                     Err(e) => {
                                         eprintln!("[bluetooth-plugin] Error decoding PropertiesChanged body: {:?}", e);
-                                        app.emit("bluetooth-change", BluetoothChange {
-                                            change_type: "error".to_string(),
-                                            data: serde_json::json!({ "message": format!("Error decoding PropertiesChanged: {:?}", e) }),
-                                        }).unwrap_or_else(|err| eprintln!("[bluetooth-plugin] Failed to emit error: {}", err));
+                                        emit_change(&app, BluetoothEvent::Error {
+                                            message: format!("Error decoding PropertiesChanged: {:?}", e),
+                                        });
                                     }
                                 }
                             } else {
                                 eprintln!("[bluetooth-plugin] PropertiesChanged signal received without a valid path.");
-                                app.emit("bluetooth-change", BluetoothChange {
-                                    change_type: "error".to_string(),
-                                    data: serde_json::json!({ "message": "PropertiesChanged signal without path" }),
-                                }).unwrap_or_else(|err| eprintln!("[bluetooth-plugin] Failed to emit error: {}", err));
+                                emit_change(&app, BluetoothEvent::Error {
+                                    message: "PropertiesChanged signal without path".to_string(),
+                                });
                             }
                         }
                         (Some("org.bluez.Device1"), Some("Disconnected")) => {
                             if let Some(p_str) = path_opt_string {
-                                match get_device_info(p_str.clone()).await {
+                                // Auto-reconnect follows PropertiesChanged(Connected) only, so
+                                // one disconnection never starts two loops
+                                match device_snapshot(&app, &p_str).await {
                                     Ok(device_info) => {
-                                        app.emit("bluetooth-change", BluetoothChange {
-                                            change_type: "device-disconnected".to_string(),
-                                            data: serde_json::to_value(device_info).unwrap_or_default(),
-                                        }).unwrap_or_else(|e| eprintln!("[bluetooth-plugin] Failed to emit device-disconnected: {}", e));
+                                        emit_change(&app, BluetoothEvent::DeviceDisconnected {
+                                            path: p_str,
+                                            device: Some(device_info),
+                                        });
                                     }
                                     Err(e) => {
                                         eprintln!("[bluetooth-plugin] Error getting device info for disconnected device {}: {:?}", p_str, e);
-                                        app.emit("bluetooth-change", BluetoothChange {
-                                            change_type: "device-disconnected".to_string(),
-                                            data: serde_json::json!({ "path": p_str }),
-                                        }).unwrap_or_else(|err| eprintln!("[bluetooth-plugin] Failed to emit fallback device-disconnected: {}", err));
+                                        emit_change(&app, BluetoothEvent::DeviceDisconnected { path: p_str, device: None });
                                     }
                                 }
                             }
                         }
                         (Some("org.bluez.Device1"), Some("Connected")) => {
                             if let Some(p_str) = path_opt_string {
-                                match get_device_info(p_str.clone()).await {
+                                match device_snapshot(&app, &p_str).await {
                                     Ok(device_info) => {
-                                        app.emit("bluetooth-change", BluetoothChange {
-                                            change_type: "device-connected".to_string(),
-                                            data: serde_json::to_value(device_info).unwrap_or_default(),
-                                        }).unwrap_or_else(|e| eprintln!("[bluetooth-plugin] Failed to emit device-connected: {}", e));
+                                        emit_change(&app, BluetoothEvent::DeviceConnected {
+                                            path: p_str,
+                                            device: Some(device_info),
+                                        });
                                     }
                                     Err(e) => {
                                         eprintln!("[bluetooth-plugin] Error getting device info for connected device {}: {:?}", p_str, e);
-                                        app.emit("bluetooth-change", BluetoothChange {
-                                            change_type: "device-connected".to_string(),
-                                            data: serde_json::json!({ "path": p_str, "connected": true }),
-                                        }).unwrap_or_else(|err| eprintln!("[bluetooth-plugin] Failed to emit fallback device-connected: {}", err));
+                                        emit_change(&app, BluetoothEvent::DeviceConnected { path: p_str, device: None });
                                     }
                                 }
                             }
@@ -381,10 +608,9 @@ async fn run_signal_listener<R: Runtime>(conn: Connection, app: AppHandle<R>) {
           }
           Err(e) => {
             eprintln!("[bluetooth-plugin] Error reading from D-Bus message stream: {:?}", e);
-            app.emit("bluetooth-change", BluetoothChange {
-                change_type: "dbus-error".to_string(),
-                data: serde_json::json!({ "message": format!("D-Bus stream error: {:?}", e) }),
-            }).unwrap_or_else(|err| eprintln!("[bluetooth-plugin] Failed to emit dbus-error: {}", err));
+            emit_change(&app, BluetoothEvent::ListenerStopped {
+                message: format!("D-Bus stream error: {:?}", e),
+            });
             break;
           }
         }
@@ -400,4 +626,556 @@ impl BluetoothManager {
             value: payload.value,
         })
     }
+
+    /// Registers a custom profile and returns the connections BlueZ hands over as byte streams.
+    pub async fn register_profile_streams<R: Runtime>(
+        &self,
+        app: AppHandle<R>,
+        options: ProfileOptions,
+    ) -> CrateResult<tokio::sync::mpsc::UnboundedReceiver<ProfileStream>> {
+        self.custom_profiles.register_streams(app, &self.conn, options).await
+    }
+
+    /// Opens an SPP connection to `address` and returns it as a message stream.
+    pub async fn spp_connect<R: Runtime>(
+        &self,
+        app: AppHandle<R>,
+        address: &str,
+        options: SppOptions,
+    ) -> CrateResult<SppStream> {
+        self.spp
+            .connect_stream(app, &self.conn, &self.custom_profiles, address, options)
+            .await
+    }
+
+    /// Every `bluetooth-change` event, typed, as it is emitted to the webviews. A receiver
+    /// that falls too far behind skips ahead with `RecvError::Lagged`.
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<BluetoothEvent> {
+        self.events.subscribe()
+    }
+
+    // ------------------------------------------------------------------------
+    // Adapters
+    // ------------------------------------------------------------------------
+
+    pub async fn list_adapters(&self) -> CrateResult<Vec<AdapterInfo>> {
+        fetch_adapters(&self.conn).await
+    }
+
+    /// `adapter_path` may be `"default"`, like everywhere else.
+    pub async fn get_adapter_state(&self, adapter_path: &str) -> CrateResult<AdapterInfo> {
+        let adapter_path = self.default_adapter.resolve(adapter_path.to_string()).await?;
+        let proxy = Proxy::new(
+            &self.conn,
+            "org.bluez",
+            adapter_path.as_str(),
+            "org.freedesktop.DBus.Properties",
+        )
+        .await?;
+        let reply = proxy.call_method("GetAll", &("org.bluez.Adapter1",)).await?;
+        let props: HashMap<String, OwnedValue> = reply.body().deserialize()?;
+        Ok(helper_adapter_info_from_props(adapter_path, &props))
+    }
+
+    /// Powering on first lifts a soft rfkill block.
+    pub async fn set_adapter_powered(&self, adapter_path: &str, powered: bool) -> CrateResult<()> {
+        let adapter_path = self.default_adapter.resolve(adapter_path.to_string()).await?;
+        if powered {
+            crate::rfkill::unblock_for_power_on(&adapter_path).await?;
+        }
+
+        let proxy = Proxy::new(
+            &self.conn,
+            "org.bluez",
+            adapter_path.as_str(),
+            "org.freedesktop.DBus.Properties",
+        )
+        .await?;
+
+        // Right after an unblock BlueZ may still see the radio as blocked for a moment
+        let mut attempts = 0;
+        loop {
+            match proxy
+                .call_method(
+                    "Set",
+                    &("org.bluez.Adapter1", "Powered", ZbusValue::from(powered)),
+                )
+                .await
+            {
+                Ok(_) => return Ok(()),
+                Err(e) if e.to_string().contains("rfkill") => {
+                    attempts += 1;
+                    if !powered || attempts >= 5 {
+                        error!("Error powering {}: {}", adapter_path, e);
+                        return Err(crate::Error::RfkillBlocked(adapter_path));
+                    }
+                    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    pub async fn get_default_adapter(&self) -> CrateResult<Option<AdapterInfo>> {
+        self.default_adapter.get().await
+    }
+
+    /// Remembers `adapter` (object path or address) as the default; `None` clears the choice.
+    pub async fn set_default_adapter<R: Runtime>(
+        &self,
+        app: AppHandle<R>,
+        adapter: Option<String>,
+    ) -> CrateResult<()> {
+        self.default_adapter.set(&app, adapter).await
+    }
+
+    // ------------------------------------------------------------------------
+    // Scan
+    // ------------------------------------------------------------------------
+
+    /// Starts discovery. With `channel`, the results of this scan are streamed to it: the
+    /// cached devices first, then `found`/`updated`/`lost` with sequence numbers, until
+    /// `stop_scan`. The `scanDefaults` discovery filter, if any, is applied first.
+    pub async fn start_scan(
+        &self,
+        adapter_path: &str,
+        channel: Option<Channel<ScanEvent>>,
+        stale_timeout_ms: Option<u64>,
+    ) -> CrateResult<()> {
+        let adapter_path = self.default_adapter.resolve(adapter_path.to_string()).await?;
+        info!("Starting scan on adapter: {}", adapter_path);
+        if let Some(channel) = channel {
+            self.scans.start(
+                self,
+                &adapter_path,
+                channel,
+                stale_timeout_ms.unwrap_or(self.config.scan_defaults.stale_timeout_ms),
+            );
+        }
+
+        // BlueZ stops discovery when the client that started it leaves the bus, so this has
+        // to be the plugin's long-lived connection
+        let proxy = Proxy::new(&self.conn, "org.bluez", adapter_path.as_str(), "org.bluez.Adapter1").await?;
+        let defaults = &self.config.scan_defaults;
+        if defaults.has_discovery_filter() {
+            let mut filter: HashMap<&str, ZbusValue> = HashMap::new();
+            if let Some(transport) = &defaults.transport {
+                filter.insert("Transport", ZbusValue::from(transport.as_str()));
+            }
+            if let Some(rssi) = defaults.rssi {
+                filter.insert("RSSI", ZbusValue::from(rssi));
+            }
+            if let Some(duplicate_data) = defaults.duplicate_data {
+                filter.insert("DuplicateData", ZbusValue::from(duplicate_data));
+            }
+            if let Err(e) = proxy.call_method("SetDiscoveryFilter", &(filter,)).await {
+                error!("Error setting discovery filter: {}", e);
+                self.scans.stop(&adapter_path);
+                return Err(e.into());
+            }
+        }
+        match proxy.call_method("StartDiscovery", &()).await {
+            Ok(_) => {
+                info!("Scan started successfully");
+                Ok(())
+            }
+            Err(e) => {
+                let msg = e.to_string();
+                if msg.contains("InProgress") {
+                    info!("Scan already in progress, continuing...");
+                    Ok(())
+                } else {
+                    error!("Error starting scan: {}", msg);
+                    self.scans.stop(&adapter_path);
+                    Err(e.into())
+                }
+            }
+        }
+    }
+
+    pub async fn stop_scan(&self, adapter_path: &str) -> CrateResult<()> {
+        let adapter_path = self.default_adapter.resolve(adapter_path.to_string()).await?;
+        info!("Stopping scan on adapter: {}", adapter_path);
+        self.scans.stop(&adapter_path);
+
+        let proxy = Proxy::new(&self.conn, "org.bluez", adapter_path.as_str(), "org.bluez.Adapter1").await?;
+        match proxy.call_method("StopDiscovery", &()).await {
+            Ok(_) => {
+                info!("Scan stopped successfully");
+                Ok(())
+            }
+            Err(e) => {
+                let msg = e.to_string();
+                if msg.contains("No discovery started")
+                    || msg.contains("org.bluez.Error.Failed")
+                    || msg.contains("org.bluez.Error.NotReady")
+                {
+                    info!("No active scan to stop, continuing...");
+                    Ok(())
+                } else {
+                    error!("Error stopping scan: {}", msg);
+                    Err(e.into())
+                }
+            }
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Devices
+    // ------------------------------------------------------------------------
+
+    /// Every device BlueZ knows on the adapter, with the plugin's history.
+    pub async fn list_devices(&self, adapter_path: &str) -> CrateResult<Vec<DeviceInfo>> {
+        let adapter_path = self.default_adapter.resolve(adapter_path.to_string()).await?;
+        let prefix = format!("{}/", adapter_path);
+
+        let proxy = Proxy::new(&self.conn, "org.bluez", "/", "org.freedesktop.DBus.ObjectManager").await?;
+        let reply = proxy.call_method("GetManagedObjects", &()).await?;
+        let objects: HashMap<OwnedObjectPath, HashMap<String, HashMap<String, OwnedValue>>> =
+            reply.body().deserialize()?;
+
+        let mut devices: Vec<DeviceInfo> = objects
+            .iter()
+            .filter(|(path, _)| path.as_str().starts_with(&prefix))
+            .filter_map(|(path, interfaces)| {
+                interfaces.get("org.bluez.Device1").map(|props| {
+                    helper_device_info_from_props(path.to_string(), props, interfaces.get("org.bluez.Input1"))
+                })
+            })
+            .collect();
+        devices.sort_by(|a, b| a.path.cmp(&b.path));
+        info!("Found {} device(s) on {}", devices.len(), adapter_path);
+        self.device_history.annotate_all(&mut devices);
+        Ok(devices)
+    }
+
+    pub async fn list_paired_devices(&self, adapter_path: &str) -> CrateResult<Vec<DeviceInfo>> {
+        let mut devices = self.list_devices(adapter_path).await?;
+        devices.retain(|d| d.paired);
+        Ok(devices)
+    }
+
+    pub async fn get_device_info(&self, device_path: &str) -> CrateResult<DeviceInfo> {
+        let mut device = fetch_device_info(&self.conn, device_path.to_string()).await?;
+        self.device_history.annotate(&mut device);
+        Ok(device)
+    }
+
+    /// Current object path of the device with `address`. `adapter_hint` (path, address or
+    /// "default") picks the copy on that adapter when several adapters know the device.
+    pub async fn resolve_device_path(&self, address: &str, adapter_hint: Option<&str>) -> CrateResult<String> {
+        let adapter_hint = match adapter_hint {
+            Some(hint) => Some(self.default_adapter.resolve(hint.to_string()).await?),
+            None => None,
+        };
+        crate::device_lookup::resolve_device_path(&self.conn, address, adapter_hint.as_deref()).await
+    }
+
+    pub async fn get_device_by_address(&self, address: &str, adapter_hint: Option<&str>) -> CrateResult<DeviceInfo> {
+        let device_path = self.resolve_device_path(address, adapter_hint).await?;
+        self.get_device_info(&device_path).await
+    }
+
+    pub async fn set_device_trusted(&self, device_path: &str, trusted: bool) -> CrateResult<()> {
+        let proxy = Proxy::new(&self.conn, "org.bluez", device_path, "org.freedesktop.DBus.Properties").await?;
+        proxy
+            .call_method("Set", &("org.bluez.Device1", "Trusted", ZbusValue::from(trusted)))
+            .await?;
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------------
+    // Connections
+    // ------------------------------------------------------------------------
+
+    /// Plain `Connect`; "in progress" and "already connected" count as success.
+    pub async fn connect_device(&self, device_path: &str) -> CrateResult<()> {
+        let proxy = Proxy::new(&self.conn, "org.bluez", device_path, "org.bluez.Device1").await?;
+        match proxy.call_method("Connect", &()).await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let msg = e.to_string();
+                if msg.contains("InProgress")
+                    || msg.contains("br-connection-busy")
+                    || msg.contains("AlreadyConnected")
+                    || msg.contains("br-connection-already-connected")
+                {
+                    info!("Device already connecting or connected, continuing...");
+                    Ok(())
+                } else {
+                    error!("Error connecting to device: {}", msg);
+                    Err(e.into())
+                }
+            }
+        }
+    }
+
+    /// `connect_device` with a timeout, retries and cancellation (`cancel_connect`); unlike
+    /// it, fails unless the device really ends up connected.
+    pub async fn connect_device_with_options<R: Runtime>(
+        &self,
+        app: AppHandle<R>,
+        device_path: &str,
+        options: ConnectOptions,
+    ) -> CrateResult<DeviceInfo> {
+        let mut device =
+            crate::connect::connect_with_options(&app, &self.conn, &self.connect_attempts, device_path, options)
+                .await?;
+        self.device_history.annotate(&mut device);
+        Ok(device)
+    }
+
+    pub fn cancel_connect(&self, id: &str) -> CrateResult<()> {
+        self.connect_attempts.cancel(id)
+    }
+
+    /// User-requested disconnect; suspends auto-reconnect for the device.
+    pub async fn disconnect_device<R: Runtime>(&self, app: AppHandle<R>, device_path: &str) -> CrateResult<()> {
+        if let Some(address) = address_from_path(device_path) {
+            self.auto_reconnect.on_user_disconnect(&app, &address);
+        }
+        let proxy = Proxy::new(&self.conn, "org.bluez", device_path, "org.bluez.Device1").await?;
+        match proxy.call_method("Disconnect", &()).await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let msg = e.to_string();
+                if msg.contains("NotConnected")
+                    || msg.contains("br-connection-not-connected")
+                    || msg.contains("br-connection-already-disconnected")
+                {
+                    info!("Device already disconnected, continuing...");
+                    Ok(())
+                } else {
+                    error!("Error disconnecting device: {}", msg);
+                    Err(e.into())
+                }
+            }
+        }
+    }
+
+    /// `uuid` may be a full UUID, a 16-bit short form or a friendly name (`a2dp-sink`, ...).
+    pub async fn connect_profile(&self, device_path: &str, uuid: &str) -> CrateResult<()> {
+        let device = fetch_device_info(&self.conn, device_path.to_string()).await?;
+        let profile_uuid = resolve_profile_uuid(uuid, &device.uuids)?;
+        info!("Connecting profile {} on device {}", profile_uuid, device_path);
+
+        let proxy = Proxy::new(&self.conn, "org.bluez", device_path, "org.bluez.Device1").await?;
+        match proxy.call_method("ConnectProfile", &(profile_uuid.as_str(),)).await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let msg = e.to_string();
+                if msg.contains("InProgress")
+                    || msg.contains("AlreadyConnected")
+                    || msg.contains("br-connection-already-connected")
+                {
+                    info!("Profile already connecting or connected, continuing...");
+                    Ok(())
+                } else {
+                    error!("Error connecting profile {}: {}", profile_uuid, msg);
+                    Err(e.into())
+                }
+            }
+        }
+    }
+
+    pub async fn disconnect_profile(&self, device_path: &str, uuid: &str) -> CrateResult<()> {
+        let device = fetch_device_info(&self.conn, device_path.to_string()).await?;
+        let profile_uuid = resolve_profile_uuid(uuid, &device.uuids)?;
+        info!("Disconnecting profile {} on device {}", profile_uuid, device_path);
+
+        let proxy = Proxy::new(&self.conn, "org.bluez", device_path, "org.bluez.Device1").await?;
+        match proxy.call_method("DisconnectProfile", &(profile_uuid.as_str(),)).await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let msg = e.to_string();
+                if msg.contains("NotConnected") || msg.contains("br-connection-not-connected") {
+                    info!("Profile already disconnected, continuing...");
+                    Ok(())
+                } else {
+                    error!("Error disconnecting profile {}: {}", profile_uuid, msg);
+                    Err(e.into())
+                }
+            }
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // PAN
+    // ------------------------------------------------------------------------
+
+    /// Connects to a device's PAN service and returns the created interface name (e.g. "bnep0").
+    ///
+    /// BlueZ tears the connection down when the calling D-Bus client goes away, so this uses
+    /// the plugin's long-lived system bus connection instead of a per-call one.
+    pub async fn network_connect(&self, device_path: &str, role: &str) -> CrateResult<String> {
+        let role = normalize_network_role(role)?;
+        info!("Connecting PAN {} on device {}", role, device_path);
+
+        let proxy = Proxy::new(&self.conn, "org.bluez", device_path, "org.bluez.Network1").await?;
+        match proxy.call_method("Connect", &(role.as_str(),)).await {
+            Ok(reply) => {
+                let interface: String = reply.body().deserialize()?;
+                info!("PAN connected on interface {}", interface);
+                Ok(interface)
+            }
+            Err(e) => {
+                let msg = e.to_string();
+                if msg.contains("AlreadyConnected") || msg.contains("InProgress") {
+                    info!("PAN already connected, reading current interface...");
+                    let props_proxy = Proxy::new(
+                        &self.conn,
+                        "org.bluez",
+                        device_path,
+                        "org.freedesktop.DBus.Properties",
+                    )
+                    .await?;
+                    let reply = props_proxy
+                        .call_method("Get", &("org.bluez.Network1", "Interface"))
+                        .await?;
+                    let value: OwnedValue = reply.body().deserialize()?;
+                    Ok(String::try_from(value)?)
+                } else {
+                    error!("Error connecting PAN: {}", msg);
+                    Err(e.into())
+                }
+            }
+        }
+    }
+
+    pub async fn network_disconnect(&self, device_path: &str) -> CrateResult<()> {
+        let proxy = Proxy::new(&self.conn, "org.bluez", device_path, "org.bluez.Network1").await?;
+        match proxy.call_method("Disconnect", &()).await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let msg = e.to_string();
+                if msg.contains("NotConnected") {
+                    info!("PAN already disconnected, continuing...");
+                    Ok(())
+                } else {
+                    error!("Error disconnecting PAN: {}", msg);
+                    Err(e.into())
+                }
+            }
+        }
+    }
+
+    /// The registration lives as long as the plugin's system bus connection.
+    pub async fn network_server_register(&self, adapter_path: String, role: &str, bridge: &str) -> CrateResult<()> {
+        let adapter_path = self.default_adapter.resolve(adapter_path).await?;
+        let role = normalize_network_role(role)?;
+        info!("Registering PAN {} server on {} with bridge {}", role, adapter_path, bridge);
+
+        let proxy = Proxy::new(&self.conn, "org.bluez", adapter_path.as_str(), "org.bluez.NetworkServer1").await?;
+        match proxy.call_method("Register", &(role.as_str(), bridge)).await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let msg = e.to_string();
+                if msg.contains("AlreadyExists") {
+                    info!("PAN server already registered, continuing...");
+                    Ok(())
+                } else {
+                    error!("Error registering PAN server: {}", msg);
+                    Err(e.into())
+                }
+            }
+        }
+    }
+
+    pub async fn network_server_unregister(&self, adapter_path: String, role: &str) -> CrateResult<()> {
+        let adapter_path = self.default_adapter.resolve(adapter_path).await?;
+        let role = normalize_network_role(role)?;
+        let proxy = Proxy::new(&self.conn, "org.bluez", adapter_path.as_str(), "org.bluez.NetworkServer1").await?;
+        match proxy.call_method("Unregister", &(role.as_str(),)).await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let msg = e.to_string();
+                if msg.contains("DoesNotExist") {
+                    info!("PAN server not registered, continuing...");
+                    Ok(())
+                } else {
+                    error!("Error unregistering PAN server: {}", msg);
+                    Err(e.into())
+                }
+            }
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // OBEX
+    // ------------------------------------------------------------------------
+
+    pub async fn send_file<R: Runtime>(
+        &self,
+        app: AppHandle<R>,
+        device_address: String,
+        file_path: String,
+    ) -> CrateResult<ObexTransfer> {
+        info!("Sending {} to {} over OBEX", file_path, device_address);
+        crate::obex::send_file(app, device_address, file_path).await
+    }
+
+    pub async fn pbap_list(
+        &self,
+        device_address: &str,
+        location: &str,
+        phonebook: &str,
+        offset: Option<u16>,
+        max_count: Option<u16>,
+    ) -> CrateResult<Vec<PhonebookEntry>> {
+        info!("Listing PBAP {}/{} on {}", location, phonebook, device_address);
+        crate::pbap::list(device_address, location, phonebook, offset, max_count).await
+    }
+
+    pub async fn pbap_pull_all<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        device_address: &str,
+        location: &str,
+        phonebook: &str,
+        offset: Option<u16>,
+        max_count: Option<u16>,
+    ) -> CrateResult<Vec<VCardContact>> {
+        crate::pbap::pull_all(app, device_address, location, phonebook, offset, max_count).await
+    }
+
+    pub async fn pbap_pull<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        device_address: &str,
+        location: &str,
+        phonebook: &str,
+        handle: &str,
+    ) -> CrateResult<VCardContact> {
+        crate::pbap::pull(app, device_address, location, phonebook, handle).await
+    }
+
+    // ------------------------------------------------------------------------
+    // Pairing
+    // ------------------------------------------------------------------------
+
+    /// Pairs through the plugin's agent; requests arrive as `pairing-request` events and
+    /// are answered with `pairing_respond`.
+    pub async fn pair_device<R: Runtime>(&self, app: AppHandle<R>, device_path: &str) -> CrateResult<()> {
+        info!("Pairing device: {}", device_path);
+        self.pairing_agent
+            .ensure_registered(app, &self.conn, &self.config.agent_capability)
+            .await?;
+        crate::agent::pair_device(&self.conn, device_path).await
+    }
+
+    pub async fn cancel_pairing(&self, device_path: &str) -> CrateResult<()> {
+        info!("Cancelling pairing with: {}", device_path);
+        crate::agent::cancel_pairing(&self.conn, device_path).await
+    }
+
+    pub fn pairing_respond(&self, device_path: &str, accept: bool, value: Option<String>) -> CrateResult<()> {
+        self.pairing_agent.respond(device_path, accept, value)
+    }
+
+    /// Pairs, trusts and connects a HID device in one flow.
+    pub async fn setup_hid_device<R: Runtime>(&self, app: AppHandle<R>, device_path: &str) -> CrateResult<DeviceInfo> {
+        let mut device = crate::hid::setup_device(app, self, device_path).await?;
+        self.device_history.annotate(&mut device);
+        Ok(device)
+    }
 }
\ No newline at end of file
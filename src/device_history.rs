@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+use tracing::error;
+
+use crate::desktop::BluetoothManager;
+use crate::device_lookup::normalize_address;
+use crate::models::{DeviceHistory, DeviceInfo, DeviceMetadataUpdate};
+use crate::{Error, Result};
+
+const STORE_FILE: &str = "bluetooth-device-history.json";
+/// RSSI updates only mark the store dirty; it is written at most this often.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+/// Oldest plain entries are dropped past this; favorites and named devices are kept.
+const MAX_RECORDS: usize = 1000;
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn new_record(address: &str, now: u64) -> DeviceHistory {
+    DeviceHistory {
+        address: address.to_string(),
+        name: None,
+        first_seen: now,
+        last_seen: now,
+        last_connected: None,
+        last_rssi: None,
+        connection_count: 0,
+        nickname: None,
+        tags: Vec::new(),
+        favorite: false,
+    }
+}
+
+/// What a signal or a fetch told about a device.
+pub struct Sighting {
+    pub rssi: Option<i16>,
+    pub name: Option<String>,
+    pub connected: bool,
+    /// The device connected just now; counts a connection.
+    pub new_connection: bool,
+}
+
+impl Sighting {
+    pub fn from_device(device: &DeviceInfo) -> Self {
+        Self {
+            rssi: device.rssi,
+            name: device.alias.clone().or_else(|| device.name.clone()),
+            connected: device.connected,
+            new_connection: false,
+        }
+    }
+}
+
+/// What the plugin remembers about devices between runs, keyed by address (device id),
+/// stored in the app data dir. BlueZ forgets unpaired devices within minutes.
+#[derive(Default)]
+pub struct DeviceHistoryStore {
+    records: Mutex<HashMap<String, DeviceHistory>>,
+    dirty: Mutex<bool>,
+    store: Mutex<Option<PathBuf>>,
+    /// Serializes writers: the listener, the metadata commands, the flusher and exit.
+    saving: Mutex<()>,
+}
+
+impl DeviceHistoryStore {
+    pub fn load<R: Runtime>(&self, app: &AppHandle<R>) {
+        let Ok(dir) = app.path().app_data_dir() else {
+            return;
+        };
+        let path = dir.join(STORE_FILE);
+        match std::fs::read(&path) {
+            Ok(content) => match serde_json::from_slice::<Vec<DeviceHistory>>(&content) {
+                Ok(records) => self
+                    .records
+                    .lock()
+                    .unwrap()
+                    .extend(records.into_iter().map(|r| (r.address.clone(), r))),
+                Err(e) => {
+                    // Kept for recovery; the next save would otherwise overwrite it
+                    let aside = path.with_file_name(format!("{}.invalid-{}", STORE_FILE, now_ms()));
+                    error!("Invalid {}: {}; moving it to {}", path.display(), e, aside.display());
+                    if let Err(e) = std::fs::rename(&path, &aside) {
+                        error!("Error moving {} aside: {}; device history won't be saved", path.display(), e);
+                        return;
+                    }
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                error!("Error reading {}: {}; device history won't be saved", path.display(), e);
+                return;
+            }
+        }
+        *self.store.lock().unwrap() = Some(path);
+    }
+
+    /// Writes the store every `FLUSH_INTERVAL` when something changed.
+    pub fn spawn_flusher<R: Runtime>(app: AppHandle<R>) {
+        tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::time::sleep(FLUSH_INTERVAL).await;
+                app.state::<BluetoothManager>().device_history.flush();
+            }
+        });
+    }
+
+    /// Writes the store if something changed since the last save; called on exit.
+    pub fn flush(&self) {
+        if *self.dirty.lock().unwrap() {
+            if let Err(e) = self.save() {
+                error!("Error saving device history: {}", e);
+            }
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = self.store.lock().unwrap().clone() else {
+            return Ok(());
+        };
+        let _saving = self.saving.lock().unwrap();
+        let mut records: Vec<DeviceHistory> = {
+            let mut map = self.records.lock().unwrap();
+            if map.len() > MAX_RECORDS {
+                let mut plain: Vec<(u64, String)> = map
+                    .values()
+                    .filter(|r| !r.favorite && r.nickname.is_none() && r.tags.is_empty())
+                    .map(|r| (r.last_seen, r.address.clone()))
+                    .collect();
+                plain.sort();
+                for (_, address) in plain.into_iter().take(map.len() - MAX_RECORDS) {
+                    map.remove(&address);
+                }
+            }
+            map.values().cloned().collect()
+        };
+        *self.dirty.lock().unwrap() = false;
+        records.sort_by(|a, b| a.address.cmp(&b.address));
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| Error::CommandError(format!("Error creating {}: {}", dir.display(), e)))?;
+        }
+        // Written next to the store and renamed over it, so a crash never leaves half a file
+        let content = serde_json::to_string_pretty(&records).unwrap_or_default();
+        let tmp = path.with_file_name(format!("{}.tmp", STORE_FILE));
+        std::fs::write(&tmp, content)
+            .map_err(|e| Error::CommandError(format!("Error writing {}: {}", tmp.display(), e)))?;
+        std::fs::rename(&tmp, &path)
+            .map_err(|e| Error::CommandError(format!("Error replacing {}: {}", path.display(), e)))
+    }
+
+    /// Records a sighting. Devices without RSSI that aren't connected are only cached by
+    /// BlueZ, not actually around, and are ignored.
+    pub fn observe(&self, address: &str, sighting: Sighting) {
+        if sighting.rssi.is_none() && !sighting.connected {
+            return;
+        }
+        let now = now_ms();
+        {
+            let mut records = self.records.lock().unwrap();
+            let record = records
+                .entry(address.to_string())
+                .or_insert_with(|| new_record(address, now));
+            record.last_seen = now;
+            if sighting.rssi.is_some() {
+                record.last_rssi = sighting.rssi;
+            }
+            if let Some(name) = sighting.name {
+                record.name = Some(name);
+            }
+            if sighting.new_connection {
+                record.connection_count += 1;
+                record.last_connected = Some(now);
+            }
+        }
+
+        if sighting.new_connection {
+            if let Err(e) = self.save() {
+                error!("Error saving device history: {}", e);
+            }
+        } else {
+            *self.dirty.lock().unwrap() = true;
+        }
+    }
+
+    /// Fills `device.history` from the store.
+    pub fn annotate(&self, device: &mut DeviceInfo) {
+        device.history = self.records.lock().unwrap().get(&device.id).cloned();
+    }
+
+    pub fn annotate_all(&self, devices: &mut [DeviceInfo]) {
+        let records = self.records.lock().unwrap();
+        for device in devices {
+            device.history = records.get(&device.id).cloned();
+        }
+    }
+
+    pub fn get(&self, address: &str) -> Result<Option<DeviceHistory>> {
+        let address = normalize_address(address)?;
+        Ok(self.records.lock().unwrap().get(&address).cloned())
+    }
+
+    /// Most recently seen first, optionally only favorites or devices with `tag`.
+    pub fn list(&self, favorites_only: bool, tag: Option<&str>) -> Vec<DeviceHistory> {
+        let mut list: Vec<DeviceHistory> = self
+            .records
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|r| !favorites_only || r.favorite)
+            .filter(|r| tag.map(|t| r.tags.iter().any(|rt| rt == t)).unwrap_or(true))
+            .cloned()
+            .collect();
+        list.sort_by_key(|r| std::cmp::Reverse(r.last_seen));
+        list
+    }
+
+    /// Applies user metadata; an empty nickname clears it.
+    pub fn update(&self, address: &str, update: DeviceMetadataUpdate) -> Result<DeviceHistory> {
+        let address = normalize_address(address)?;
+        let record = {
+            let mut records = self.records.lock().unwrap();
+            let record = records
+                .entry(address.clone())
+                .or_insert_with(|| new_record(&address, now_ms()));
+            if let Some(nickname) = update.nickname {
+                record.nickname = Some(nickname).filter(|n| !n.is_empty());
+            }
+            if let Some(mut tags) = update.tags {
+                tags.sort();
+                tags.dedup();
+                record.tags = tags;
+            }
+            if let Some(favorite) = update.favorite {
+                record.favorite = favorite;
+            }
+            record.clone()
+        };
+        self.save()?;
+        Ok(record)
+    }
+
+    pub fn forget(&self, address: &str) -> Result<()> {
+        let address = normalize_address(address)?;
+        if self.records.lock().unwrap().remove(&address).is_none() {
+            return Err(Error::NotFound(format!("No history for {}", address)));
+        }
+        self.save()
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use zbus::{
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue},
+    Connection, Proxy,
+};
+
+use crate::{Error, Result};
+
+/// Stable identifier for a device, independent of the adapter it shows up on.
+///
+/// BlueZ reports the identity address for bonded LE devices, so the address survives
+/// adapter renumbering and moving the device to another dongle.
+pub fn device_id(address: &str) -> String {
+    address.to_uppercase()
+}
+
+/// Validates and normalizes a MAC address to `AA:BB:CC:DD:EE:FF`.
+pub fn normalize_address(address: &str) -> Result<String> {
+    let address = address.trim().replace('-', ":").to_uppercase();
+    let valid = address.len() == 17
+        && address.split(':').count() == 6
+        && address
+            .split(':')
+            .all(|byte| byte.len() == 2 && byte.chars().all(|c| c.is_ascii_hexdigit()));
+    if valid {
+        Ok(address)
+    } else {
+        Err(Error::CommandError(format!("Invalid Bluetooth address: {}", address)))
+    }
+}
+
+/// Address encoded in a BlueZ device path (`/org/bluez/hci0/dev_AA_BB_CC_DD_EE_FF`).
+pub fn address_from_path(device_path: &str) -> Option<String> {
+    let node = device_path.rsplit('/').next()?.strip_prefix("dev_")?;
+    normalize_address(&node.replace('_', ":")).ok()
+}
+
+struct Candidate {
+    path: String,
+    adapter: String,
+    connected: bool,
+    paired: bool,
+}
+
+/// Resolves a device address to its current object path.
+///
+/// `adapter_hint` (an adapter path or address) is preferred when the device is known to
+/// several adapters; otherwise connected, then paired copies win.
+pub async fn resolve_device_path(
+    conn: &Connection,
+    address: &str,
+    adapter_hint: Option<&str>,
+) -> Result<String> {
+    let address = normalize_address(address)?;
+
+    let proxy = Proxy::new(conn, "org.bluez", "/", "org.freedesktop.DBus.ObjectManager").await?;
+    let reply = proxy.call_method("GetManagedObjects", &()).await?;
+    let objects: HashMap<OwnedObjectPath, HashMap<String, HashMap<String, OwnedValue>>> =
+        reply.body().deserialize()?;
+
+    let hint_path = adapter_hint.map(|hint| {
+        if hint.starts_with('/') {
+            hint.to_string()
+        } else {
+            objects
+                .iter()
+                .find(|(_, interfaces)| {
+                    interfaces
+                        .get("org.bluez.Adapter1")
+                        .and_then(|props| props.get("Address"))
+                        .and_then(|v| String::try_from(&**v).ok())
+                        .map(|a| a.eq_ignore_ascii_case(hint))
+                        .unwrap_or(false)
+                })
+                .map(|(path, _)| path.to_string())
+                .unwrap_or_default()
+        }
+    });
+
+    let mut candidates: Vec<Candidate> = objects
+        .iter()
+        .filter_map(|(path, interfaces)| {
+            let props = interfaces.get("org.bluez.Device1")?;
+            let device_address = props.get("Address").and_then(|v| String::try_from(&**v).ok())?;
+            if !device_address.eq_ignore_ascii_case(&address) {
+                return None;
+            }
+            Some(Candidate {
+                path: path.to_string(),
+                adapter: props
+                    .get("Adapter")
+                    .and_then(|v| ObjectPath::try_from(&**v).ok())
+                    .map(|p| p.to_string())
+                    .unwrap_or_default(),
+                connected: props.get("Connected").and_then(|v| bool::try_from(&**v).ok()).unwrap_or(false),
+                paired: props.get("Paired").and_then(|v| bool::try_from(&**v).ok()).unwrap_or(false),
+            })
+        })
+        .collect();
+
+    candidates.sort_by(|a, b| {
+        let hinted = |c: &Candidate| hint_path.as_deref() == Some(c.adapter.as_str());
+        hinted(b)
+            .cmp(&hinted(a))
+            .then(b.connected.cmp(&a.connected))
+            .then(b.paired.cmp(&a.paired))
+            .then(a.path.cmp(&b.path))
+    });
+
+    candidates
+        .into_iter()
+        .next()
+        .map(|c| c.path)
+        .ok_or_else(|| Error::NotFound(format!("Device not found: {}", address)))
+}
@@ -11,6 +11,20 @@ pub enum Error {
     CommandError(String),
     #[error("Not found: {0}")]
     NotFound(String),
+    #[error("OBEX service not available: {0}")]
+    ObexUnavailable(String),
+    #[error("Transfer rejected: {0}")]
+    TransferRejected(String),
+    #[error("Pairing failed: {0}")]
+    PairingFailed(String),
+    #[error("Blocked through rfkill: {0}")]
+    RfkillBlocked(String),
+    #[error("Connection failed: {0}")]
+    ConnectionFailed(String),
+    #[error("Timed out: {0}")]
+    Timeout(String),
+    #[error("Canceled: {0}")]
+    Canceled(String),
 }
 
 impl serde::Serialize for Error {
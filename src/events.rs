@@ -0,0 +1,177 @@
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tokio::sync::broadcast;
+
+use crate::desktop::BluetoothManager;
+use crate::models::{AdapterInfo, BluetoothChange, DeviceInfo, RfkillState};
+
+/// Events a slow receiver can fall behind by before it gets `RecvError::Lagged`.
+const CHANNEL_CAPACITY: usize = 256;
+
+type PropertyMap = serde_json::Map<String, serde_json::Value>;
+
+/// Typed `bluetooth-change` events for Rust code, from `BluetoothManager::subscribe`.
+///
+/// Event types without a variant of their own arrive as `Other` with the raw payload.
+#[derive(Debug, Clone)]
+pub enum BluetoothEvent {
+    AdapterAdded(AdapterInfo),
+    AdapterRemoved {
+        path: String,
+    },
+    AdapterPropertyChanged {
+        path: String,
+        changed: PropertyMap,
+        invalidated: Vec<String>,
+        adapter: Option<AdapterInfo>,
+    },
+    /// `None` when the last adapter went away.
+    DefaultAdapterChanged(Option<AdapterInfo>),
+    DeviceAdded(DeviceInfo),
+    /// `paired` is the state the device had before BlueZ dropped it.
+    DeviceRemoved {
+        path: String,
+        paired: bool,
+    },
+    /// `device` is missing if the snapshot could not be taken.
+    DeviceConnected {
+        path: String,
+        device: Option<DeviceInfo>,
+    },
+    DeviceDisconnected {
+        path: String,
+        device: Option<DeviceInfo>,
+    },
+    /// After throttling, as sent to the webviews.
+    DevicePropertyChanged {
+        path: String,
+        address: Option<String>,
+        changed: PropertyMap,
+        invalidated: Vec<String>,
+        device: Option<DeviceInfo>,
+    },
+    RfkillChanged(RfkillState),
+    /// An rfkill switch went away (`rfkill-changed` with `removed: true`).
+    RfkillRemoved {
+        index: u32,
+        name: String,
+    },
+    Error {
+        message: String,
+    },
+    /// The D-Bus signal stream ended; no further BlueZ events will follow.
+    ListenerStopped {
+        message: String,
+    },
+    Other(BluetoothChange),
+}
+
+fn to_json<T: serde::Serialize>(value: T) -> serde_json::Value {
+    serde_json::to_value(value).unwrap_or_default()
+}
+
+impl BluetoothEvent {
+    pub fn other(change_type: &str, data: serde_json::Value) -> Self {
+        BluetoothEvent::Other(BluetoothChange {
+            change_type: change_type.to_string(),
+            data,
+        })
+    }
+
+    /// The `bluetooth-change` payload the webviews get.
+    pub fn to_change(&self) -> BluetoothChange {
+        let (change_type, data) = match self {
+            BluetoothEvent::AdapterAdded(adapter) => ("adapter-added", to_json(adapter)),
+            BluetoothEvent::AdapterRemoved { path } => ("adapter-removed", serde_json::json!({ "path": path })),
+            BluetoothEvent::AdapterPropertyChanged { path, changed, invalidated, adapter } => (
+                "adapter-property-changed",
+                serde_json::json!({
+                    "path": path,
+                    "changed": changed,
+                    "invalidated": invalidated,
+                    "adapter": adapter,
+                }),
+            ),
+            BluetoothEvent::DefaultAdapterChanged(adapter) => ("default-adapter-changed", to_json(adapter)),
+            BluetoothEvent::DeviceAdded(device) => ("device-added", to_json(device)),
+            BluetoothEvent::DeviceRemoved { path, paired } => (
+                "device-removed",
+                serde_json::json!({ "path": path, "paired": paired }),
+            ),
+            BluetoothEvent::DeviceConnected { path, device } => (
+                "device-connected",
+                match device {
+                    Some(device) => to_json(device),
+                    None => serde_json::json!({ "path": path, "connected": true }),
+                },
+            ),
+            BluetoothEvent::DeviceDisconnected { path, device } => (
+                "device-disconnected",
+                match device {
+                    Some(device) => to_json(device),
+                    None => serde_json::json!({ "path": path }),
+                },
+            ),
+            BluetoothEvent::DevicePropertyChanged { path, address, changed, invalidated, device } => {
+                let mut data = serde_json::json!({
+                    "path": path,
+                    "address": address,
+                    "changed": changed,
+                    "invalidated": invalidated,
+                });
+                if let Some(device) = device {
+                    data["device"] = to_json(device);
+                }
+                ("device-property-changed", data)
+            }
+            BluetoothEvent::RfkillChanged(state) => ("rfkill-changed", to_json(state)),
+            BluetoothEvent::RfkillRemoved { index, name } => (
+                "rfkill-changed",
+                serde_json::json!({ "index": index, "name": name, "removed": true }),
+            ),
+            BluetoothEvent::Error { message } => ("error", serde_json::json!({ "message": message })),
+            BluetoothEvent::ListenerStopped { message } => ("dbus-error", serde_json::json!({ "message": message })),
+            BluetoothEvent::Other(change) => return change.clone(),
+        };
+        BluetoothChange {
+            change_type: change_type.to_string(),
+            data,
+        }
+    }
+}
+
+/// Fan-out of every `bluetooth-change` event to in-process receivers.
+pub struct EventBus {
+    sender: broadcast::Sender<BluetoothEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(CHANNEL_CAPACITY).0,
+        }
+    }
+}
+
+impl EventBus {
+    pub fn subscribe(&self) -> broadcast::Receiver<BluetoothEvent> {
+        self.sender.subscribe()
+    }
+
+    fn publish(&self, event: BluetoothEvent) {
+        // Nobody listening is not an error
+        let _ = self.sender.send(event);
+    }
+}
+
+/// The one way events leave the plugin: `bluetooth-change` to the webviews, the matching
+/// Channel subscriptions and the typed Rust receivers.
+pub fn emit_change<R: Runtime>(app: &AppHandle<R>, event: BluetoothEvent) {
+    let change = event.to_change();
+    if let Some(manager) = app.try_state::<BluetoothManager>() {
+        manager.subscriptions.dispatch(&change, &manager.objects);
+        manager.events.publish(event);
+    }
+    let change_type = change.change_type.clone();
+    app.emit("bluetooth-change", change)
+        .unwrap_or_else(|e| eprintln!("[bluetooth-plugin] Failed to emit {}: {}", change_type, e));
+}
@@ -0,0 +1,199 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Runtime};
+use tracing::{error, info};
+use zbus::{
+    zvariant::{OwnedValue, Value as ZbusValue},
+    Connection, Proxy,
+};
+
+use crate::agent::pair_device;
+use crate::desktop::{fetch_device_info, BluetoothManager};
+use crate::events::{emit_change, BluetoothEvent};
+use crate::models::{DeviceInfo, HidInfo};
+use crate::profiles::uuid_from_u16;
+use crate::{Error, Result};
+
+const HID_CLASSIC: u16 = 0x1124;
+const HID_HOGP: u16 = 0x1812;
+const SERVICES_RESOLVED_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Builds the HID summary from the device UUIDs and, when exported, its `Input1` properties.
+pub fn hid_info(uuids: &[String], input: Option<&HashMap<String, OwnedValue>>) -> Option<HidInfo> {
+    let has = |short: u16| {
+        let uuid = uuid_from_u16(short);
+        uuids.iter().any(|u| u.eq_ignore_ascii_case(&uuid))
+    };
+    let transport = if has(HID_HOGP) {
+        "hogp"
+    } else if has(HID_CLASSIC) || input.is_some() {
+        "classic"
+    } else {
+        return None;
+    };
+
+    Some(HidInfo {
+        transport: transport.to_string(),
+        reconnect_mode: input
+            .and_then(|props| props.get("ReconnectMode"))
+            .and_then(|v| String::try_from(&**v).ok()),
+        // BlueZ does not export the SDP HIDVirtualCable attribute
+        virtual_cable: None,
+    })
+}
+
+/// Reads `org.bluez.Input1` properties; `None` until BlueZ has set up the input device.
+pub async fn input_props(conn: &Connection, device_path: &str) -> Option<HashMap<String, OwnedValue>> {
+    let proxy = Proxy::new(conn, "org.bluez", device_path, "org.freedesktop.DBus.Properties")
+        .await
+        .ok()?;
+    let reply = proxy.call_method("GetAll", &("org.bluez.Input1",)).await.ok()?;
+    let body = reply.body();
+    body.deserialize().ok()
+}
+
+/// Rejects devices that clearly aren't HID. Unknown devices (no UUIDs, class or
+/// appearance yet) are let through, pairing will tell.
+fn ensure_hid_candidate(device: &DeviceInfo) -> Result<()> {
+    let major_class_peripheral = device.class.map(|c| (c >> 8) & 0x1F == 0x05);
+    let appearance_hid = device.appearance.map(|a| a >> 6 == 0x0F);
+    let unknown = device.uuids.is_empty() && device.class.is_none() && device.appearance.is_none();
+
+    if unknown
+        || device.hid.is_some()
+        || major_class_peripheral == Some(true)
+        || appearance_hid == Some(true)
+    {
+        Ok(())
+    } else {
+        Err(Error::CommandError(format!("{} is not a HID device", device.path)))
+    }
+}
+
+/// Devices with a HID setup in progress, so two flows never race on the same device.
+#[derive(Default)]
+pub struct HidSetups {
+    in_progress: Mutex<HashSet<String>>,
+}
+
+struct SetupGuard<'a> {
+    setups: &'a HidSetups,
+    device_path: String,
+}
+
+impl Drop for SetupGuard<'_> {
+    fn drop(&mut self) {
+        self.setups.in_progress.lock().unwrap().remove(&self.device_path);
+    }
+}
+
+impl HidSetups {
+    fn begin(&self, device_path: &str) -> Result<SetupGuard<'_>> {
+        if !self.in_progress.lock().unwrap().insert(device_path.to_string()) {
+            return Err(Error::CommandError(format!(
+                "HID setup already in progress for {}",
+                device_path
+            )));
+        }
+        Ok(SetupGuard {
+            setups: self,
+            device_path: device_path.to_string(),
+        })
+    }
+}
+
+fn emit_step<R: Runtime>(app: &AppHandle<R>, device_path: &str, step: &str, error: Option<String>) {
+    emit_change(app, BluetoothEvent::other("hid-setup-progress", serde_json::json!({ "device": device_path, "step": step, "error": error })));
+}
+
+async fn set_trusted(conn: &Connection, device_path: &str) -> Result<()> {
+    let proxy = Proxy::new(conn, "org.bluez", device_path, "org.freedesktop.DBus.Properties").await?;
+    proxy
+        .call_method("Set", &("org.bluez.Device1", "Trusted", ZbusValue::from(true)))
+        .await?;
+    Ok(())
+}
+
+async fn connect(conn: &Connection, device_path: &str) -> Result<()> {
+    let proxy = Proxy::new(conn, "org.bluez", device_path, "org.bluez.Device1").await?;
+    match proxy.call_method("Connect", &()).await {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            let msg = e.to_string();
+            if msg.contains("InProgress") || msg.contains("AlreadyConnected") || msg.contains("already-connected") {
+                Ok(())
+            } else {
+                Err(e.into())
+            }
+        }
+    }
+}
+
+async fn wait_services_resolved(conn: &Connection, device_path: &str) -> Result<DeviceInfo> {
+    let deadline = tokio::time::Instant::now() + SERVICES_RESOLVED_TIMEOUT;
+    loop {
+        let device = fetch_device_info(conn, device_path.to_string()).await?;
+        if (device.connected && device.services_resolved) || tokio::time::Instant::now() >= deadline {
+            return Ok(device);
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+}
+
+async fn run_setup<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &BluetoothManager,
+    device_path: &str,
+) -> Result<DeviceInfo> {
+    let device = fetch_device_info(&state.conn, device_path.to_string()).await?;
+    ensure_hid_candidate(&device)?;
+
+    if !device.paired {
+        // Keyboards get a `pairing-display` event with the passkey to type on them
+        state
+            .pairing_agent
+            .ensure_registered(app.clone(), &state.conn, &state.config.agent_capability)
+            .await?;
+        emit_step(app, device_path, "pairing", None);
+        pair_device(&state.conn, device_path).await?;
+    }
+
+    if !device.trusted {
+        // Trusted lets the device reconnect on its own after sleep or power cycles
+        emit_step(app, device_path, "trusting", None);
+        set_trusted(&state.conn, device_path).await?;
+    }
+
+    emit_step(app, device_path, "connecting", None);
+    connect(&state.conn, device_path).await?;
+
+    emit_step(app, device_path, "resolving-services", None);
+    let device = wait_services_resolved(&state.conn, device_path).await?;
+    if !device.connected {
+        return Err(Error::CommandError(format!("{} did not stay connected", device_path)));
+    }
+    Ok(device)
+}
+
+/// Pair, trust and connect a HID device in one go, reporting each step.
+pub async fn setup_device<R: Runtime>(
+    app: AppHandle<R>,
+    state: &BluetoothManager,
+    device_path: &str,
+) -> Result<DeviceInfo> {
+    let _guard = state.hid_setups.begin(device_path)?;
+    info!("Setting up HID device {}", device_path);
+
+    match run_setup(&app, state, device_path).await {
+        Ok(device) => {
+            emit_step(&app, device_path, "done", None);
+            Ok(device)
+        }
+        Err(e) => {
+            error!("HID setup for {} failed: {}", device_path, e);
+            emit_step(&app, device_path, "failed", Some(e.to_string()));
+            Err(e)
+        }
+    }
+}
@@ -1,20 +1,49 @@
+use std::path::PathBuf;
 use tauri::{
     async_runtime,
-    plugin::{Builder, TauriPlugin},
-    Manager, Runtime,
+    plugin::{Builder as PluginBuilder, TauriPlugin},
+    Manager, RunEvent, Runtime,
 };
 
 pub use models::*;
 
+mod adv_monitor;
+mod agent;
+mod bmessage;
 mod commands;
+mod connect;
+mod custom_profiles;
+mod default_adapter;
 mod desktop;
+mod device_history;
+mod device_lookup;
 mod error;
+mod events;
+mod hid;
 mod models;
 mod logging;
+mod map;
+mod obex;
+mod obex_agent;
+mod obex_ftp;
+mod object_cache;
+mod pbap;
+mod presence;
+mod profiles;
+mod reconnect;
+mod rfkill;
+mod scan;
+mod spp;
+mod subscriptions;
+mod throttle;
+mod vcard;
 
+pub use custom_profiles::ProfileStream;
 pub use error::{Error, Result};
+pub use events::BluetoothEvent;
+pub use spp::SppStream;
 
-use desktop::BluetoothManager;
+pub use desktop::BluetoothManager;
 
 pub trait BluetoothManagerExt<R: Runtime> {
     fn bluetooth_manager(&self) -> &BluetoothManager;
@@ -26,35 +55,215 @@ impl<R: Runtime, T: Manager<R>> BluetoothManagerExt<R> for T {
     }
 }
 
-/// Initializes the plugin.
-pub fn init<R: Runtime>() -> TauriPlugin<R> {
-    logging::init_logging();
-
-    Builder::<R>::new("bluetooth-manager")
-        .invoke_handler(tauri::generate_handler![
-            commands::list_adapters,
-            commands::set_adapter_powered,
-            commands::get_adapter_state,
-            commands::start_scan,
-            commands::stop_scan,
-            commands::list_devices,
-            commands::list_paired_devices,
-            commands::connect_device,
-            commands::disconnect_device,
-            commands::get_device_info,
-            commands::bluetooth_plugin_status,
-        ])
-        .setup(|app_handle, api| {
-            let result = async_runtime::block_on(desktop::init(app_handle.clone(), api));
-            let initialized = result.is_ok();
-            if let Some(manager) = app_handle.try_state::<desktop::BluetoothManager>() {
-                let mut guard = manager.inner().initialized.lock().unwrap();
-                *guard = initialized;
-            }
-            if let Err(e) = result {
-                tracing::error!("Bluetooth service not available: {e}");
-            }
-            Ok(())
-        })
-        .build()
+/// Initializes the plugin with the `plugins.bluetooth-manager` configuration.
+pub fn init<R: Runtime>() -> TauriPlugin<R, Option<PluginConfig>> {
+    Builder::new().build()
+}
+
+/// Initializes the plugin with custom `device-property-changed` rate limits; they can be
+/// changed later with `set_event_throttle`.
+#[deprecated(since = "2.0.3", note = "use `Builder::new().throttle(throttle).build()`")]
+pub fn init_with_throttle<R: Runtime>(throttle: ThrottleConfig) -> TauriPlugin<R, Option<PluginConfig>> {
+    Builder::new().throttle(throttle).build()
+}
+
+/// Plugin options set in code. Each one set here replaces the value from
+/// `plugins.bluetooth-manager` in tauri.conf.json.
+#[derive(Default)]
+pub struct Builder {
+    log_file: Option<PathBuf>,
+    log_filter: Option<String>,
+    auto_power_on: Option<bool>,
+    agent_capability: Option<String>,
+    scan_defaults: Option<ScanDefaults>,
+    throttle: Option<ThrottleConfig>,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Defaults to `~/.logs/vasak/bluetooth.log`.
+    pub fn log_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.log_file = Some(path.into());
+        self
+    }
+
+    /// An `EnvFilter` directive such as `tauri_plugin_bluetooth_manager=debug`; `RUST_LOG`
+    /// still wins.
+    pub fn log_filter(mut self, filter: impl Into<String>) -> Self {
+        self.log_filter = Some(filter.into());
+        self
+    }
+
+    /// Powers on adapters that are off at startup or when plugged in.
+    pub fn auto_power_on(mut self, enabled: bool) -> Self {
+        self.auto_power_on = Some(enabled);
+        self
+    }
+
+    /// IO capability of the pairing agent: `DisplayOnly`, `DisplayYesNo`, `KeyboardOnly`,
+    /// `NoInputNoOutput` or `KeyboardDisplay` (the default).
+    pub fn agent_capability(mut self, capability: impl Into<String>) -> Self {
+        self.agent_capability = Some(capability.into());
+        self
+    }
+
+    /// Stale timeout and discovery filter used by `start_scan`.
+    pub fn scan_defaults(mut self, defaults: ScanDefaults) -> Self {
+        self.scan_defaults = Some(defaults);
+        self
+    }
+
+    /// Initial `device-property-changed` rate limits.
+    pub fn throttle(mut self, throttle: ThrottleConfig) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    fn apply(self, config: &mut PluginConfig) {
+        if let Some(log_file) = self.log_file {
+            config.log_file = Some(log_file);
+        }
+        if let Some(log_filter) = self.log_filter {
+            config.log_filter = Some(log_filter);
+        }
+        if let Some(auto_power_on) = self.auto_power_on {
+            config.auto_power_on = auto_power_on;
+        }
+        if let Some(agent_capability) = self.agent_capability {
+            config.agent_capability = agent_capability;
+        }
+        if let Some(scan_defaults) = self.scan_defaults {
+            config.scan_defaults = scan_defaults;
+        }
+        if let Some(throttle) = self.throttle {
+            config.throttle = throttle;
+        }
+    }
+
+    pub fn build<R: Runtime>(self) -> TauriPlugin<R, Option<PluginConfig>> {
+        PluginBuilder::<R, Option<PluginConfig>>::new("bluetooth-manager")
+            .invoke_handler(tauri::generate_handler![
+                commands::list_adapters,
+                commands::set_adapter_powered,
+                commands::get_default_adapter,
+                commands::set_default_adapter,
+                commands::get_rfkill_state,
+                commands::rfkill_block,
+                commands::rfkill_unblock,
+                commands::get_adapter_state,
+                commands::start_scan,
+                commands::stop_scan,
+                commands::register_advertisement_monitor,
+                commands::unregister_advertisement_monitor,
+                commands::list_advertisement_monitors,
+                commands::list_devices,
+                commands::list_paired_devices,
+                commands::connect_device,
+                commands::connect_device_with_options,
+                commands::cancel_connect,
+                commands::disconnect_device,
+                commands::set_auto_reconnect,
+                commands::list_auto_reconnect,
+                commands::pair_device,
+                commands::cancel_pairing,
+                commands::pairing_respond,
+                commands::set_device_trusted,
+                commands::setup_hid_device,
+                commands::connect_profile,
+                commands::disconnect_profile,
+                commands::network_connect,
+                commands::network_disconnect,
+                commands::network_server_register,
+                commands::network_server_unregister,
+                commands::register_profile,
+                commands::unregister_profile,
+                commands::list_profile_connections,
+                commands::profile_write,
+                commands::profile_close,
+                commands::spp_connect,
+                commands::spp_write,
+                commands::spp_pause,
+                commands::spp_resume,
+                commands::spp_close,
+                commands::list_spp_connections,
+                commands::send_file,
+                commands::cancel_transfer,
+                commands::register_obex_agent,
+                commands::unregister_obex_agent,
+                commands::accept_incoming_transfer,
+                commands::reject_incoming_transfer,
+                commands::get_obex_receive_config,
+                commands::set_obex_receive_config,
+                commands::ftp_connect,
+                commands::ftp_disconnect,
+                commands::ftp_list_folder,
+                commands::ftp_change_folder,
+                commands::ftp_create_folder,
+                commands::ftp_delete,
+                commands::ftp_get_file,
+                commands::ftp_put_file,
+                commands::pbap_list,
+                commands::pbap_pull_all,
+                commands::pbap_pull,
+                commands::map_connect,
+                commands::map_disconnect,
+                commands::map_list_folders,
+                commands::map_list_messages,
+                commands::map_get_message,
+                commands::map_set_message_read,
+                commands::map_push_message,
+                commands::get_device_info,
+                commands::resolve_device_path,
+                commands::get_device_by_address,
+                commands::list_device_history,
+                commands::get_device_history,
+                commands::set_device_metadata,
+                commands::forget_device_history,
+                commands::get_device_proximity,
+                commands::list_device_proximity,
+                commands::get_presence_config,
+                commands::set_presence_config,
+                commands::get_event_throttle,
+                commands::set_event_throttle,
+                commands::subscribe_events,
+                commands::unsubscribe_events,
+                commands::list_event_subscriptions,
+                commands::bluetooth_plugin_status,
+            ])
+            .setup(move |app_handle, api| {
+                let mut config = api.config().clone().unwrap_or_default();
+                self.apply(&mut config);
+                logging::init_logging(config.log_file.as_deref(), config.log_filter.as_deref());
+                if !agent::AGENT_CAPABILITIES.contains(&config.agent_capability.as_str()) {
+                    tracing::error!(
+                        "Unknown agent capability {}, using {}",
+                        config.agent_capability,
+                        agent::DEFAULT_AGENT_CAPABILITY
+                    );
+                    config.agent_capability = agent::DEFAULT_AGENT_CAPABILITY.to_string();
+                }
+
+                let result = async_runtime::block_on(desktop::init(app_handle.clone(), config));
+                let initialized = result.is_ok();
+                if let Some(manager) = app_handle.try_state::<desktop::BluetoothManager>() {
+                    let mut guard = manager.inner().initialized.lock().unwrap();
+                    *guard = initialized;
+                }
+                if let Err(e) = result {
+                    tracing::error!("Bluetooth service not available: {e}");
+                }
+                Ok(())
+            })
+            .on_event(|app_handle, event| {
+                if let RunEvent::Exit = event {
+                    if let Some(manager) = app_handle.try_state::<desktop::BluetoothManager>() {
+                        manager.device_history.flush();
+                    }
+                }
+            })
+            .build()
+    }
 }
@@ -1,34 +1,55 @@
 use std::sync::OnceLock;
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_FILTER: &str = "tauri_plugin_bluetooth_manager=info";
 
 static INIT_LOGGING: OnceLock<()> = OnceLock::new();
 
-pub fn init_logging() {
+/// `log_file` and `filter` default to `~/.logs/vasak/bluetooth.log` and info level for
+/// the plugin; `RUST_LOG` overrides the filter.
+///
+/// Does nothing if the app already installed a global subscriber: the plugin's events go
+/// to that one instead.
+pub fn init_logging(log_file: Option<&Path>, filter: Option<&str>) {
     INIT_LOGGING.get_or_init(|| {
-        let log_file_path = get_log_path();
+        if tracing::dispatcher::has_been_set() {
+            return;
+        }
+
+        let log_file_path = log_file.map(Path::to_path_buf).unwrap_or_else(get_log_path);
 
         // Crear directorio si no existe
         if let Some(parent) = log_file_path.parent() {
             let _ = std::fs::create_dir_all(parent);
         }
 
+        // Sin archivo de log se sigue solo con stdout
         let file = std::fs::OpenOptions::new()
             .create(true)
             .append(true)
             .open(&log_file_path)
-            .expect("Failed to open log file");
+            .map_err(|e| {
+                eprintln!(
+                    "[bluetooth-plugin] Failed to open log file {}: {}; logging to stdout only",
+                    log_file_path.display(),
+                    e
+                )
+            })
+            .ok();
 
         let env_filter = EnvFilter::try_from_default_env()
-            .unwrap_or_else(|_| EnvFilter::new("tauri_plugin_bluetooth_manager=info"));
+            .unwrap_or_else(|_| EnvFilter::new(filter.unwrap_or(DEFAULT_FILTER)));
 
-        let file_layer = fmt::layer()
-            .with_writer(file)
-            .with_target(true)
-            .with_thread_ids(false)
-            .with_ansi(false)
-            .pretty()
-            .boxed();
+        let file_layer = file.map(|file| {
+            fmt::layer()
+                .with_writer(file)
+                .with_target(true)
+                .with_thread_ids(false)
+                .with_ansi(false)
+                .pretty()
+                .boxed()
+        });
 
         let stdout_layer = fmt::layer()
             .with_writer(std::io::stdout)
@@ -36,11 +57,14 @@ pub fn init_logging() {
             .with_ansi(true)
             .boxed();
 
-        tracing_subscriber::registry()
+        if let Err(e) = tracing_subscriber::registry()
             .with(env_filter)
             .with(file_layer)
             .with(stdout_layer)
-            .init();
+            .try_init()
+        {
+            eprintln!("[bluetooth-plugin] Keeping the existing tracing subscriber: {}", e);
+        }
     });
 }
 
@@ -0,0 +1,367 @@
+use futures::StreamExt;
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{async_runtime::JoinHandle, AppHandle, Runtime};
+use tracing::{error, info};
+use zbus::{
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value as ZbusValue},
+    Connection, Proxy,
+};
+
+use crate::bmessage::{build_sms_bmessage, parse_bmessage};
+use crate::events::{emit_change, BluetoothEvent};
+use crate::models::{BMessage, MapMessage, MapMessageFilter};
+use crate::obex::{
+    create_session, map_obex_error, private_temp_path, remove_session, transfer_from_props,
+    wait_transfer, OBEX_SERVICE,
+};
+use crate::{Error, Result};
+
+/// Root of the message folders on every MAP server.
+const MAP_MESSAGES_ROOT: &str = "/telecom/msg";
+
+macro_rules! get_prop {
+    ($props:expr, $key:expr, $ty:ty) => {
+        $props.get($key).and_then(|v| <$ty>::try_from(&**v).ok())
+    };
+    ($props:expr, $key:expr, $ty:ty, $default:expr) => {
+        $props.get($key).and_then(|v| <$ty>::try_from(&**v).ok()).unwrap_or($default)
+    };
+}
+
+fn message_from_props(path: String, props: &HashMap<String, OwnedValue>) -> MapMessage {
+    MapMessage {
+        path,
+        folder: get_prop!(props, "Folder", String),
+        subject: get_prop!(props, "Subject", String),
+        timestamp: get_prop!(props, "Timestamp", String),
+        sender: get_prop!(props, "Sender", String),
+        sender_address: get_prop!(props, "SenderAddress", String),
+        recipient: get_prop!(props, "Recipient", String),
+        recipient_address: get_prop!(props, "RecipientAddress", String),
+        message_type: get_prop!(props, "Type", String),
+        size: get_prop!(props, "Size", u64),
+        status: get_prop!(props, "Status", String),
+        read: get_prop!(props, "Read", bool, false),
+        sent: get_prop!(props, "Sent", bool, false),
+    }
+}
+
+#[derive(Clone)]
+struct MapHandle {
+    conn: Connection,
+    path: OwnedObjectPath,
+    // Message objects created while a listing is running are not notifications
+    listing: Arc<AtomicUsize>,
+    known: Arc<Mutex<HashSet<String>>>,
+    // The current folder is per session: held from SetFolder until the call that uses it
+    folder: Arc<tokio::sync::Mutex<()>>,
+}
+
+struct MapSession {
+    handle: MapHandle,
+    notifications: JoinHandle<()>,
+}
+
+/// Persistent MAP sessions kept inside `BluetoothManager`, keyed by device address.
+///
+/// Message object paths are only valid while their session lives, so a session stays
+/// open until `disconnect` and also receives MNS notifications for new messages.
+#[derive(Default)]
+pub struct MapSessions {
+    sessions: Mutex<HashMap<String, MapSession>>,
+}
+
+impl MapSessions {
+    pub async fn connect<R: Runtime>(&self, app: AppHandle<R>, device_address: &str) -> Result<()> {
+        let key = device_address.to_ascii_uppercase();
+        if self.sessions.lock().unwrap().contains_key(&key) {
+            return Ok(());
+        }
+
+        let conn = Connection::session().await?;
+        let path = create_session(&conn, device_address, "map").await?;
+        let listing = Arc::new(AtomicUsize::new(0));
+        let known = Arc::new(Mutex::new(HashSet::new()));
+
+        {
+            let mut sessions = self.sessions.lock().unwrap();
+            // A concurrent connect finished first; keep its session
+            if let Entry::Vacant(entry) = sessions.entry(key) {
+                let notifications = tauri::async_runtime::spawn(watch_new_messages(
+                    conn.clone(),
+                    app,
+                    path.to_string(),
+                    listing.clone(),
+                    known.clone(),
+                ));
+                entry.insert(MapSession {
+                    handle: MapHandle {
+                        conn,
+                        path,
+                        listing,
+                        known,
+                        folder: Arc::default(),
+                    },
+                    notifications,
+                });
+                return Ok(());
+            }
+        }
+        remove_session(&conn, &path).await
+    }
+
+    pub async fn disconnect(&self, device_address: &str) -> Result<()> {
+        let Some(session) = self
+            .sessions
+            .lock()
+            .unwrap()
+            .remove(&device_address.to_ascii_uppercase())
+        else {
+            return Ok(());
+        };
+        session.notifications.abort();
+        remove_session(&session.handle.conn, &session.handle.path).await
+    }
+
+    fn session(&self, device_address: &str) -> Result<MapHandle> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(&device_address.to_ascii_uppercase())
+            .map(|session| session.handle.clone())
+            .ok_or_else(|| {
+                Error::NotFound(format!("No MAP session for {} (call map_connect first)", device_address))
+            })
+    }
+
+    async fn access_proxy(&self, session: &MapHandle) -> Result<Proxy<'static>> {
+        let proxy = Proxy::new(
+            &session.conn,
+            OBEX_SERVICE,
+            session.path.clone(),
+            "org.bluez.obex.MessageAccess1",
+        )
+        .await?;
+        Ok(proxy)
+    }
+
+    async fn set_folder(proxy: &Proxy<'_>, folder: &str) -> Result<()> {
+        proxy
+            .call_method("SetFolder", &(folder,))
+            .await
+            .map_err(map_obex_error)?;
+        Ok(())
+    }
+
+    /// Lists the subfolders of `folder` (absolute, e.g. "/telecom/msg").
+    pub async fn list_folders(&self, device_address: &str, folder: &str) -> Result<Vec<String>> {
+        let session = self.session(device_address)?;
+        let proxy = self.access_proxy(&session).await?;
+        let _folder = session.folder.lock().await;
+        Self::set_folder(&proxy, folder).await?;
+        let filter: HashMap<&str, ZbusValue> = HashMap::new();
+        let reply = proxy
+            .call_method("ListFolders", &(filter,))
+            .await
+            .map_err(map_obex_error)?;
+        let folders: Vec<HashMap<String, OwnedValue>> = reply.body().deserialize()?;
+        Ok(folders
+            .iter()
+            .filter_map(|f| get_prop!(f, "Name", String))
+            .collect())
+    }
+
+    /// Lists messages in `folder` ("inbox", "sent", ... or an absolute path).
+    pub async fn list_messages(
+        &self,
+        device_address: &str,
+        folder: &str,
+        filter: MapMessageFilter,
+    ) -> Result<Vec<MapMessage>> {
+        let session = self.session(device_address)?;
+        let proxy = self.access_proxy(&session).await?;
+
+        let (parent, name) = match folder.rsplit_once('/') {
+            Some((parent, name)) if folder.starts_with('/') => {
+                (if parent.is_empty() { "/" } else { parent }, name)
+            }
+            _ => (MAP_MESSAGES_ROOT, folder),
+        };
+        let folder_guard = session.folder.lock().await;
+        Self::set_folder(&proxy, parent).await?;
+
+        let mut args: HashMap<&str, ZbusValue> = HashMap::new();
+        if let Some(offset) = filter.offset {
+            args.insert("Offset", ZbusValue::from(offset));
+        }
+        if let Some(max_count) = filter.max_count {
+            args.insert("MaxCount", ZbusValue::from(max_count));
+        }
+        if filter.unread_only.unwrap_or(false) {
+            args.insert("Read", ZbusValue::from(false));
+        }
+        if let Some(begin) = filter.period_begin.as_deref() {
+            args.insert("PeriodBegin", ZbusValue::from(begin));
+        }
+        if let Some(end) = filter.period_end.as_deref() {
+            args.insert("PeriodEnd", ZbusValue::from(end));
+        }
+        if let Some(sender) = filter.sender.as_deref() {
+            args.insert("Sender", ZbusValue::from(sender));
+        }
+
+        session.listing.fetch_add(1, Ordering::SeqCst);
+        let reply = proxy.call_method("ListMessages", &(name, args)).await;
+        session.listing.fetch_sub(1, Ordering::SeqCst);
+        drop(folder_guard);
+        let reply = reply.map_err(map_obex_error)?;
+
+        let messages: HashMap<OwnedObjectPath, HashMap<String, OwnedValue>> =
+            reply.body().deserialize()?;
+        info!("MAP session {} listed {} message(s)", session.path.as_str(), messages.len());
+
+        let mut known = session.known.lock().unwrap();
+        let mut result: Vec<MapMessage> = messages
+            .into_iter()
+            .map(|(path, props)| {
+                known.insert(path.to_string());
+                message_from_props(path.to_string(), &props)
+            })
+            .collect();
+        // Newest first; MAP timestamps ("YYYYMMDDTHHMMSS") sort lexicographically
+        result.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        Ok(result)
+    }
+
+    /// Downloads a message (`Message1.Get`) and parses the bMessage.
+    pub async fn get_message<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        device_address: &str,
+        message_path: &str,
+    ) -> Result<BMessage> {
+        let conn = self.session(device_address)?.conn;
+        let proxy = Proxy::new(&conn, OBEX_SERVICE, message_path, "org.bluez.obex.Message1").await?;
+
+        let target = private_temp_path(app, "map", "bmsg")?;
+        let reply = proxy
+            .call_method("Get", &(target.as_str(), false))
+            .await
+            .map_err(map_obex_error)?;
+        let (transfer_path, props): (OwnedObjectPath, HashMap<String, OwnedValue>) =
+            reply.body().deserialize()?;
+        let transfer = transfer_from_props(transfer_path.to_string(), &props);
+        let transfer = wait_transfer(&conn, transfer).await?;
+
+        let filename = transfer.filename.unwrap_or(target);
+        let data = std::fs::read(&filename)
+            .map_err(|e| Error::CommandError(format!("Error reading {}: {}", filename, e)));
+        let _ = std::fs::remove_file(&filename);
+        Ok(parse_bmessage(&String::from_utf8_lossy(&data?)))
+    }
+
+    pub async fn set_read(&self, device_address: &str, message_path: &str, read: bool) -> Result<()> {
+        let conn = self.session(device_address)?.conn;
+        let proxy = Proxy::new(
+            &conn,
+            OBEX_SERVICE,
+            message_path,
+            "org.freedesktop.DBus.Properties",
+        )
+        .await?;
+        proxy
+            .call_method("Set", &("org.bluez.obex.Message1", "Read", ZbusValue::from(read)))
+            .await
+            .map_err(map_obex_error)?;
+        Ok(())
+    }
+
+    /// Sends an SMS by pushing a bMessage into the outbox (`MessageAccess1.PushMessage`).
+    pub async fn push_message<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        device_address: &str,
+        recipient: &str,
+        text: &str,
+    ) -> Result<()> {
+        let message = build_sms_bmessage(recipient, text)?;
+        let session = self.session(device_address)?;
+        let conn = session.conn.clone();
+        let proxy = self.access_proxy(&session).await?;
+        let _folder = session.folder.lock().await;
+        Self::set_folder(&proxy, MAP_MESSAGES_ROOT).await?;
+
+        let source = private_temp_path(app, "map", "bmsg")?;
+        std::fs::write(&source, message)
+            .map_err(|e| Error::CommandError(format!("Error writing {}: {}", source, e)))?;
+
+        let mut args: HashMap<&str, ZbusValue> = HashMap::new();
+        args.insert("Charset", ZbusValue::from("utf8"));
+
+        let result = async {
+            let reply = proxy
+                .call_method("PushMessage", &(source.as_str(), "outbox", args))
+                .await
+                .map_err(map_obex_error)?;
+            let (transfer_path, props): (OwnedObjectPath, HashMap<String, OwnedValue>) =
+                reply.body().deserialize()?;
+            wait_transfer(&conn, transfer_from_props(transfer_path.to_string(), &props)).await
+        }
+        .await;
+
+        let _ = std::fs::remove_file(&source);
+        result.map(|_| ())
+    }
+}
+
+/// Emits `map-message-received` for Message1 objects that obexd creates from MNS
+/// notifications (objects not produced by one of our own listings).
+async fn watch_new_messages<R: Runtime>(
+    conn: Connection,
+    app: AppHandle<R>,
+    session_path: String,
+    listing: Arc<AtomicUsize>,
+    known: Arc<Mutex<HashSet<String>>>,
+) {
+    let proxy = match Proxy::new(&conn, OBEX_SERVICE, "/", "org.freedesktop.DBus.ObjectManager").await {
+        Ok(proxy) => proxy,
+        Err(e) => {
+            error!("Error watching MAP notifications: {:?}", e);
+            return;
+        }
+    };
+    let mut added = match proxy.receive_signal("InterfacesAdded").await {
+        Ok(stream) => stream,
+        Err(e) => {
+            error!("Error subscribing to MAP notifications: {:?}", e);
+            return;
+        }
+    };
+
+    let prefix = format!("{}/", session_path);
+    while let Some(msg) = added.next().await {
+        let body = msg.body();
+        let Ok((object_path, interfaces)) = body
+            .deserialize::<(ObjectPath<'_>, HashMap<String, HashMap<String, OwnedValue>>)>()
+        else {
+            continue;
+        };
+        let path = object_path.to_string();
+        if !path.starts_with(&prefix) || listing.load(Ordering::SeqCst) > 0 {
+            continue;
+        }
+        let Some(props) = interfaces.get("org.bluez.obex.Message1") else {
+            continue;
+        };
+        if !known.lock().unwrap().insert(path.clone()) {
+            continue;
+        }
+
+        let message = message_from_props(path, props);
+        info!("New MAP message: {:?}", message.path);
+        emit_change(&app, BluetoothEvent::other("map-message-received", serde_json::to_value(message).unwrap_or_default()));
+    }
+}
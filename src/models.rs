@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
 
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -29,8 +31,37 @@ pub struct AdapterInfo {
     pub modalias: Option<String>, // Ejemplo: "usb:v1D6Bp0246d0540"
 }
 
+// Snake case como DeviceInfo, que lo incluye; los alias leen los archivos guardados en camelCase
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeviceHistory {
+    pub address: String,
+    pub name: Option<String>,        // Último nombre/alias conocido
+    #[serde(alias = "firstSeen")]
+    pub first_seen: u64,             // Milisegundos Unix
+    #[serde(alias = "lastSeen")]
+    pub last_seen: u64,
+    #[serde(alias = "lastConnected")]
+    pub last_connected: Option<u64>,
+    #[serde(alias = "lastRssi")]
+    pub last_rssi: Option<i16>,
+    #[serde(alias = "connectionCount")]
+    pub connection_count: u32,
+    pub nickname: Option<String>,
+    pub tags: Vec<String>,
+    pub favorite: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceMetadataUpdate {
+    pub nickname: Option<String>, // "" lo borra
+    pub tags: Option<Vec<String>>,
+    pub favorite: Option<bool>,
+}
+
 #[derive(Serialize, Debug, Clone)]
 pub struct DeviceInfo {
+    pub id: String, // Estable entre adaptadores (dirección normalizada)
     pub path: String,
     pub address: String, // MAC address
     pub name: Option<String>,
@@ -48,12 +79,383 @@ pub struct DeviceInfo {
     pub uuids: Vec<String>,
     pub adapter: String, // ObjectPath del adaptador al que pertenece
     pub services_resolved: bool,
+    pub hid: Option<HidInfo>, // Solo para teclados, ratones, mandos...
+    pub history: Option<DeviceHistory>, // Metadatos persistidos por el plugin
     // Podríamos añadir `manufacturer_data: Option<HashMap<u16, Vec<u8>>>`
     // y `service_data: Option<HashMap<String, Vec<u8>>>` si es necesario.
 }
 
+#[derive(Serialize, Debug, Clone)]
+pub struct HidInfo {
+    pub transport: String,              // "hogp" (LE, 0x1812) o "classic" (BR/EDR, 0x1124)
+    pub reconnect_mode: Option<String>, // Input1.ReconnectMode: none, host, device, any
+    pub virtual_cable: Option<bool>,    // None si BlueZ no permite saberlo
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct BluetoothChange {
     pub change_type: String,
     pub data: serde_json::Value,
 }
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ObexTransfer {
+    pub path: String,            // ObjectPath del Transfer1 en el bus de sesión
+    pub session: Option<String>, // ObjectPath de la sesión OBEX
+    pub name: Option<String>,
+    pub filename: Option<String>,
+    pub size: Option<u64>,
+    pub transferred: u64,
+    pub status: String, // queued, active, suspended, complete, error
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IncomingTransfer {
+    pub path: String, // ObjectPath del Transfer1 entrante
+    pub device_address: Option<String>,
+    pub name: Option<String>,
+    pub size: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ObexReceiveConfig {
+    pub download_dir: String,
+    pub max_file_size: Option<u64>, // bytes; None = sin límite
+}
+
+impl Default for ObexReceiveConfig {
+    fn default() -> Self {
+        let download_dir = dirs::download_dir()
+            .or_else(|| dirs::home_dir().map(|h| h.join("Downloads")))
+            .unwrap_or_else(|| std::path::PathBuf::from("."));
+        Self {
+            download_dir: download_dir.to_string_lossy().to_string(),
+            max_file_size: None,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FtpEntry {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub entry_type: String, // "folder" o "file"
+    pub size: Option<u64>,
+    pub modified: Option<String>, // ISO 8601 (UTC) cuando el dispositivo lo informa
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PhonebookEntry {
+    pub handle: String, // Ej: "0.vcf"
+    pub name: String,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct VCardField {
+    pub value: String,
+    pub types: Vec<String>, // Ej: ["cell", "voice"]
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct VCardContact {
+    pub version: Option<String>,
+    pub uid: Option<String>,
+    pub formatted_name: Option<String>,
+    pub family_name: Option<String>,
+    pub given_name: Option<String>,
+    pub phones: Vec<VCardField>,
+    pub emails: Vec<VCardField>,
+    pub addresses: Vec<VCardField>,
+    pub organization: Option<String>,
+    pub title: Option<String>,
+    pub birthday: Option<String>,
+    pub note: Option<String>,
+    pub call_type: Option<String>,     // Historial: "missed", "received" o "dialed"
+    pub call_datetime: Option<String>, // X-IRMC-CALL-DATETIME
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MapMessage {
+    pub path: String, // ObjectPath del Message1 en la sesión MAP
+    pub folder: Option<String>,
+    pub subject: Option<String>,
+    pub timestamp: Option<String>,
+    pub sender: Option<String>,
+    pub sender_address: Option<String>,
+    pub recipient: Option<String>,
+    pub recipient_address: Option<String>,
+    pub message_type: Option<String>, // sms-gsm, sms-cdma, email, mms
+    pub size: Option<u64>,
+    pub status: Option<String>,
+    pub read: bool,
+    pub sent: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MapMessageFilter {
+    pub offset: Option<u16>,
+    pub max_count: Option<u16>,
+    pub unread_only: Option<bool>,
+    pub period_begin: Option<String>, // "YYYYMMDDTHHMMSS"
+    pub period_end: Option<String>,
+    pub sender: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BMessage {
+    pub version: Option<String>,
+    pub status: Option<String>, // READ / UNREAD
+    pub message_type: Option<String>, // SMS_GSM, SMS_CDMA, EMAIL, MMS
+    pub folder: Option<String>,
+    pub originator: Option<VCardContact>,
+    pub recipients: Vec<VCardContact>,
+    pub charset: Option<String>,
+    pub body: String,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileOptions {
+    pub uuid: String,                 // UUID completo, corto ("1101") o alias ("spp")
+    pub name: Option<String>,
+    pub role: Option<String>,         // client o server
+    pub channel: Option<u16>,         // Canal RFCOMM
+    pub psm: Option<u16>,             // PSM L2CAP
+    pub require_authentication: Option<bool>,
+    pub require_authorization: Option<bool>,
+    pub auto_connect: Option<bool>,
+    pub service_record: Option<String>, // Registro SDP en XML
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileConnection {
+    pub id: String,
+    pub uuid: String,
+    pub device: String, // ObjectPath del dispositivo
+    pub address: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoReconnectInfo {
+    pub address: String,
+    pub reconnecting: bool,      // Bucle de reconexión en curso
+    pub user_disconnected: bool, // Suspendido hasta la próxima conexión
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorPattern {
+    pub start_position: u8, // Desplazamiento dentro del dato AD
+    pub ad_type: u8,        // Tipo AD, p. ej. 0xff (Manufacturer Specific Data)
+    pub content: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AdvertisementMonitorOptions {
+    pub patterns: Vec<MonitorPattern>, // or_patterns: basta con que coincida uno
+    pub rssi_low_threshold: Option<i16>,
+    pub rssi_high_threshold: Option<i16>,
+    pub rssi_low_timeout: Option<u16>,     // Segundos bajo el umbral bajo para DeviceLost
+    pub rssi_high_timeout: Option<u16>,    // Segundos sobre el umbral alto para DeviceFound
+    pub rssi_sampling_period: Option<u16>, // En unidades de 100 ms
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AdvertisementMonitorInfo {
+    pub id: String,
+    pub adapter: String,
+    pub options: AdvertisementMonitorOptions,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanEvent {
+    pub seq: u64,                 // Número de secuencia, empieza en 0
+    pub kind: String,             // initial | found | updated | lost | stopped
+    pub path: Option<String>,     // Dispositivo (found/updated/lost)
+    pub device: Option<DeviceInfo>,
+    pub devices: Vec<DeviceInfo>, // Dispositivos en caché (solo initial)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SubscriptionFilter {
+    pub events: Vec<String>,   // Tipos de evento (changeType); vacío = todos
+    pub adapters: Vec<String>, // Rutas de adaptador, p. ej. /org/bluez/hci0
+    pub devices: Vec<String>,  // Direcciones o rutas de dispositivo
+    pub paired_only: bool,     // Solo eventos de dispositivos emparejados
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionInfo {
+    pub id: u64,
+    pub window: String, // Etiqueta de la ventana
+    pub filter: SubscriptionFilter,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ThrottleConfig {
+    pub default_interval_ms: u64,        // Intervalo mínimo por propiedad
+    pub intervals: HashMap<String, u64>, // Por propiedad, p. ej. {"RSSI": 2000}
+    pub immediate: Vec<String>,          // Propiedades que nunca se retienen
+    pub coalesce_ms: u64,                // Ventana para agrupar cambios (0 = sin ventana)
+    pub rssi: bool,                      // RSSI solo si se pide
+    pub manufacturer_data: bool,         // ManufacturerData solo si se pide
+    pub snapshot: bool,                  // Añadir el DeviceInfo completo (desde la caché)
+}
+
+impl Default for ThrottleConfig {
+    fn default() -> Self {
+        Self {
+            default_interval_ms: 500,
+            intervals: HashMap::new(),
+            immediate: ["Connected", "Paired", "Bonded", "Trusted", "Blocked", "Name", "Alias", "ServicesResolved"]
+                .iter()
+                .map(|k| k.to_string())
+                .collect(),
+            coalesce_ms: 0,
+            rssi: false,
+            manufacturer_data: false,
+            snapshot: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScanDefaults {
+    pub stale_timeout_ms: u64,        // Sin cambios durante este tiempo: lost
+    pub transport: Option<String>,    // Filtro de descubrimiento: "auto", "bredr" o "le"
+    pub rssi: Option<i16>,            // Ignorar dispositivos por debajo de este RSSI
+    pub duplicate_data: Option<bool>, // false: solo avisar si cambian los datos anunciados
+}
+
+impl Default for ScanDefaults {
+    fn default() -> Self {
+        Self {
+            stale_timeout_ms: 30_000,
+            transport: None,
+            rssi: None,
+            duplicate_data: None,
+        }
+    }
+}
+
+impl ScanDefaults {
+    pub fn has_discovery_filter(&self) -> bool {
+        self.transport.is_some() || self.rssi.is_some() || self.duplicate_data.is_some()
+    }
+}
+
+/// `plugins.bluetooth-manager` in tauri.conf.json; `Builder` values take precedence.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PluginConfig {
+    pub log_file: Option<PathBuf>,  // None: ~/.logs/vasak/bluetooth.log
+    pub log_filter: Option<String>, // Sintaxis de EnvFilter; RUST_LOG tiene prioridad
+    pub auto_power_on: bool,        // Encender los adaptadores al arrancar y al conectarlos
+    pub agent_capability: String,   // IO capability del agente de emparejamiento
+    pub scan_defaults: ScanDefaults,
+    pub throttle: ThrottleConfig,
+}
+
+impl Default for PluginConfig {
+    fn default() -> Self {
+        Self {
+            log_file: None,
+            log_filter: None,
+            auto_power_on: false,
+            agent_capability: crate::agent::DEFAULT_AGENT_CAPABILITY.to_string(),
+            scan_defaults: ScanDefaults::default(),
+            throttle: ThrottleConfig::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PresenceConfig {
+    pub near_rssi: i16,       // dBm suavizados para pasar a near
+    pub hysteresis: i16,      // dB por debajo de near_rssi para volver a far
+    pub gone_timeout_ms: u64, // Sin RSSI ni conexión durante este tiempo: gone
+    pub smoothing: f64,       // Alfa de la media móvil exponencial (0-1]
+}
+
+impl Default for PresenceConfig {
+    fn default() -> Self {
+        Self {
+            near_rssi: -65,
+            hysteresis: 8,
+            gone_timeout_ms: 30_000,
+            smoothing: 0.25,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProximityInfo {
+    pub address: String,
+    pub path: String,
+    pub state: String,         // near, far o gone
+    pub rssi: Option<f64>,     // Suavizado
+    pub raw_rssi: Option<i16>, // Última muestra
+    pub distance: Option<f64>, // Metros, estimación aproximada
+    pub connected: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SppOptions {
+    pub mode: Option<String>,      // raw (por defecto), line o framed
+    pub delimiter: Option<String>, // Fin de línea en modo line, "\n" por defecto
+    pub reconnect: Option<bool>,
+    pub max_reconnect_attempts: Option<u32>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectOptions {
+    pub id: Option<String>,                 // Para cancel_connect; por defecto la ruta del dispositivo
+    pub timeout_ms: Option<u64>,            // Por intento, 15000 por defecto
+    pub retries: Option<u32>,               // Reintentos tras el primer intento, 0 por defecto
+    pub backoff_ms: Option<u64>,            // Espera inicial entre intentos (se duplica), 1000 por defecto
+    pub wait_services_resolved: Option<bool>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SppConnectionInfo {
+    pub id: String,
+    pub address: String,
+    pub device: String, // ObjectPath del dispositivo
+    pub mode: String,
+    pub connected: bool,
+    pub paused: bool,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RfkillState {
+    pub index: u32,
+    pub name: String,            // hci0, o el interruptor de plataforma (p. ej. tpacpi_bluetooth_sw)
+    pub adapter: Option<String>, // ObjectPath del adaptador; None si afecta a todos
+    pub soft_blocked: bool,
+    pub hard_blocked: bool,
+}
@@ -0,0 +1,345 @@
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+use tracing::{error, info};
+use zbus::{
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value as ZbusValue},
+    Connection, Proxy,
+};
+
+use crate::desktop::BluetoothManager;
+use crate::events::{emit_change, BluetoothEvent};
+use crate::models::ObexTransfer;
+use crate::{Error, Result};
+
+pub const OBEX_SERVICE: &str = "org.bluez.obex";
+pub const OBEX_PATH: &str = "/org/bluez/obex";
+/// How long a vanished transfer's final `PropertiesChanged` may still be in flight.
+const FINAL_STATUS_GRACE: Duration = Duration::from_millis(500);
+
+macro_rules! get_prop {
+    ($props:expr, $key:expr, $ty:ty) => {
+        $props.get($key).and_then(|v| <$ty>::try_from(&**v).ok())
+    };
+    ($props:expr, $key:expr, $ty:ty, $default:expr) => {
+        $props.get($key).and_then(|v| <$ty>::try_from(&**v).ok()).unwrap_or($default)
+    };
+}
+
+/// Translates obexd failures into the plugin error variants the frontend can act on.
+pub fn map_obex_error(e: zbus::Error) -> Error {
+    let msg = e.to_string();
+    if msg.contains("ServiceUnknown") || msg.contains("NameHasNoOwner") {
+        Error::ObexUnavailable("org.bluez.obex is not running (is obexd installed?)".to_string())
+    } else if msg.contains("Forbidden")
+        || msg.contains("Not Acceptable")
+        || msg.contains("Refused")
+        || msg.contains("rejected")
+    {
+        Error::TransferRejected(msg)
+    } else {
+        e.into()
+    }
+}
+
+pub fn transfer_from_props(path: String, props: &HashMap<String, OwnedValue>) -> ObexTransfer {
+    ObexTransfer {
+        path,
+        session: props
+            .get("Session")
+            .and_then(|v| ObjectPath::try_from(&**v).ok())
+            .map(|p: ObjectPath| p.to_string()),
+        name: get_prop!(props, "Name", String),
+        filename: get_prop!(props, "Filename", String),
+        size: get_prop!(props, "Size", u64),
+        transferred: get_prop!(props, "Transferred", u64, 0),
+        status: get_prop!(props, "Status", String, "queued".to_string()),
+    }
+}
+
+/// Opens an OBEX client session (`Client1.CreateSession`) with the given target ("opp", "ftp", ...).
+pub async fn create_session(
+    conn: &Connection,
+    device_address: &str,
+    target: &str,
+) -> Result<OwnedObjectPath> {
+    let proxy = Proxy::new(conn, OBEX_SERVICE, OBEX_PATH, "org.bluez.obex.Client1").await?;
+
+    let mut args: HashMap<&str, ZbusValue> = HashMap::new();
+    args.insert("Target", ZbusValue::from(target));
+
+    let reply = proxy
+        .call_method("CreateSession", &(device_address, args))
+        .await
+        .map_err(map_obex_error)?;
+    let session: OwnedObjectPath = reply.body().deserialize()?;
+    info!("OBEX {} session created: {}", target, session.as_str());
+    Ok(session)
+}
+
+pub async fn remove_session(conn: &Connection, session: &ObjectPath<'_>) -> Result<()> {
+    let proxy = Proxy::new(conn, OBEX_SERVICE, OBEX_PATH, "org.bluez.obex.Client1").await?;
+    proxy
+        .call_method("RemoveSession", &(session,))
+        .await
+        .map_err(map_obex_error)?;
+    Ok(())
+}
+
+/// A fresh file name in `<app cache>/<dir>`, a directory only this user can enter, so
+/// contacts and messages pulled from a phone are never readable by anyone else while
+/// they sit on disk.
+pub fn private_temp_path<R: Runtime>(app: &AppHandle<R>, dir: &str, extension: &str) -> Result<String> {
+    let dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| Error::CommandError(format!("No cache directory: {}", e)))?
+        .join(dir);
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(&dir)
+        .and_then(|_| std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700)))
+        .map_err(|e| Error::CommandError(format!("Error preparing {}: {}", dir.display(), e)))?;
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    Ok(dir.join(format!("{}.{}", nanos, extension)).to_string_lossy().to_string())
+}
+
+/// Sends a file with `ObjectPush1.SendFile` and tracks the transfer in the background.
+pub async fn send_file<R: Runtime>(
+    app: AppHandle<R>,
+    device_address: String,
+    file_path: String,
+) -> Result<ObexTransfer> {
+    let source = Path::new(&file_path);
+    if !source.is_file() {
+        return Err(Error::NotFound(format!("File not found: {}", file_path)));
+    }
+    let source = std::fs::canonicalize(source)
+        .map_err(|e| Error::NotFound(format!("File not found: {} ({})", file_path, e)))?;
+
+    let conn = Connection::session().await?;
+    let session = create_session(&conn, &device_address, "opp").await?;
+
+    let push = Proxy::new(
+        &conn,
+        OBEX_SERVICE,
+        session.as_str(),
+        "org.bluez.obex.ObjectPush1",
+    )
+    .await?;
+
+    let reply = match push
+        .call_method("SendFile", &(source.to_string_lossy().as_ref(),))
+        .await
+    {
+        Ok(reply) => reply,
+        Err(e) => {
+            error!("SendFile error: {}", e);
+            let _ = remove_session(&conn, &session).await;
+            return Err(map_obex_error(e));
+        }
+    };
+
+    let (transfer_path, props): (OwnedObjectPath, HashMap<String, OwnedValue>) =
+        reply.body().deserialize()?;
+    let transfer = transfer_from_props(transfer_path.to_string(), &props);
+    info!("OBEX push started: {} -> {}", file_path, transfer.path);
+
+    if let Some(manager) = app.try_state::<BluetoothManager>() {
+        manager.obex_transfers.insert(&transfer.path, &conn);
+    }
+    tauri::async_runtime::spawn(watch_transfer(conn, app, transfer.clone(), Some(session)));
+
+    Ok(transfer)
+}
+
+/// Connections running transfers were started on. obexd only takes `Transfer1.Cancel`
+/// from the client that owns the session.
+#[derive(Default)]
+pub struct ObexTransfers {
+    connections: Mutex<HashMap<String, Connection>>,
+}
+
+impl ObexTransfers {
+    /// Records the owner of a transfer before `watch_transfer` follows it.
+    pub fn insert(&self, transfer_path: &str, conn: &Connection) {
+        self.connections
+            .lock()
+            .unwrap()
+            .insert(transfer_path.to_string(), conn.clone());
+    }
+
+    fn remove(&self, transfer_path: &str) {
+        self.connections.lock().unwrap().remove(transfer_path);
+    }
+
+    pub async fn cancel(&self, transfer_path: &str) -> Result<()> {
+        let conn = self
+            .connections
+            .lock()
+            .unwrap()
+            .get(transfer_path)
+            .cloned()
+            .ok_or_else(|| Error::NotFound(format!("No running transfer: {}", transfer_path)))?;
+        let proxy = Proxy::new(&conn, OBEX_SERVICE, transfer_path, "org.bluez.obex.Transfer1").await?;
+
+        match proxy.call_method("Cancel", &()).await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let msg = e.to_string();
+                if msg.contains("UnknownObject") || msg.contains("NotInProgress") {
+                    info!("Transfer {} already finished, nothing to cancel", transfer_path);
+                    Ok(())
+                } else {
+                    error!("Error cancelling transfer {}: {}", transfer_path, msg);
+                    Err(map_obex_error(e))
+                }
+            }
+        }
+    }
+}
+
+fn emit_transfer<R: Runtime>(app: &AppHandle<R>, change_type: &str, transfer: &ObexTransfer) {
+    emit_change(app, BluetoothEvent::other(change_type, serde_json::to_value(transfer).unwrap_or_default()));
+}
+
+/// Follows a `Transfer1` object until it reaches `complete` or `error`, calling
+/// `on_progress` for every intermediate update. `transfer` holds the final state.
+pub async fn follow_transfer(
+    conn: &Connection,
+    transfer: &mut ObexTransfer,
+    mut on_progress: impl FnMut(&ObexTransfer),
+) -> Result<()> {
+    let proxy = Proxy::new(
+        conn,
+        OBEX_SERVICE,
+        transfer.path.as_str(),
+        "org.freedesktop.DBus.Properties",
+    )
+    .await?;
+    let mut changes = proxy.receive_signal("PropertiesChanged").await?;
+
+    // The transfer may have progressed between the call that created it and the subscription
+    match proxy.call_method("GetAll", &("org.bluez.obex.Transfer1",)).await {
+        Ok(reply) => {
+            let props: HashMap<String, OwnedValue> = reply.body().deserialize()?;
+            let current = transfer_from_props(transfer.path.clone(), &props);
+            transfer.status = current.status;
+            transfer.transferred = current.transferred;
+            transfer.size = current.size.or(transfer.size);
+            transfer.name = current.name.or(transfer.name.take());
+            transfer.filename = current.filename.or(transfer.filename.take());
+            transfer.session = current.session.or(transfer.session.take());
+        }
+        Err(_) => {
+            // obexd drops the object right after the final status, whichever it was. Only a
+            // `complete` that reached the subscription counts as success
+            transfer.status = "error".to_string();
+            while let Ok(Some(msg)) = tokio::time::timeout(FINAL_STATUS_GRACE, changes.next()).await {
+                apply_changes(transfer, &msg);
+                if is_final(&transfer.status) {
+                    break;
+                }
+            }
+            if !is_final(&transfer.status) {
+                transfer.status = "error".to_string();
+            }
+            return Ok(());
+        }
+    }
+    on_progress(transfer);
+
+    while !is_final(&transfer.status) {
+        let Some(msg) = changes.next().await else {
+            break;
+        };
+        apply_changes(transfer, &msg);
+        if transfer.status == "active" || transfer.status == "queued" {
+            on_progress(transfer);
+        }
+    }
+    Ok(())
+}
+
+fn is_final(status: &str) -> bool {
+    status == "complete" || status == "error"
+}
+
+/// Merges a `Transfer1` `PropertiesChanged` into `transfer`.
+fn apply_changes(transfer: &mut ObexTransfer, msg: &zbus::Message) {
+    let Ok((_, changed, _)) = msg
+        .body()
+        .deserialize::<(String, HashMap<String, OwnedValue>, Vec<String>)>()
+    else {
+        return;
+    };
+    if let Some(status) = get_prop!(changed, "Status", String) {
+        transfer.status = status;
+    }
+    if let Some(transferred) = get_prop!(changed, "Transferred", u64) {
+        transfer.transferred = transferred;
+    }
+    if let Some(size) = get_prop!(changed, "Size", u64) {
+        transfer.size = Some(size);
+    }
+    if let Some(filename) = get_prop!(changed, "Filename", String) {
+        transfer.filename = Some(filename);
+    }
+}
+
+/// Waits for a transfer without emitting events; fails unless it completes.
+pub async fn wait_transfer(conn: &Connection, mut transfer: ObexTransfer) -> Result<ObexTransfer> {
+    follow_transfer(conn, &mut transfer, |_| {}).await?;
+    if transfer.status == "complete" {
+        Ok(transfer)
+    } else {
+        Err(Error::CommandError(format!("OBEX transfer failed: {}", transfer.path)))
+    }
+}
+
+/// Follows a transfer in the background, emitting `obex-transfer-progress`,
+/// `obex-transfer-complete` and `obex-transfer-error`. Register the transfer in
+/// `ObexTransfers` first so it can be cancelled.
+/// When `session` is given it is removed once the transfer ends.
+pub async fn watch_transfer<R: Runtime>(
+    conn: Connection,
+    app: AppHandle<R>,
+    mut transfer: ObexTransfer,
+    session: Option<OwnedObjectPath>,
+) {
+    let finished = follow_transfer(&conn, &mut transfer, |t| {
+        emit_transfer(&app, "obex-transfer-progress", t)
+    })
+    .await;
+
+    if let Err(e) = finished {
+        error!("Error watching transfer {}: {:?}", transfer.path, e);
+        transfer.status = "error".to_string();
+    }
+
+    if transfer.status == "complete" {
+        info!("OBEX transfer complete: {}", transfer.path);
+        emit_transfer(&app, "obex-transfer-complete", &transfer);
+    } else {
+        error!("OBEX transfer failed: {} ({})", transfer.path, transfer.status);
+        transfer.status = "error".to_string();
+        emit_transfer(&app, "obex-transfer-error", &transfer);
+    }
+
+    if let Some(manager) = app.try_state::<BluetoothManager>() {
+        manager.obex_transfers.remove(&transfer.path);
+    }
+    if let Some(session) = session {
+        let _ = remove_session(&conn, &session).await;
+    }
+}
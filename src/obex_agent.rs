@@ -0,0 +1,313 @@
+use futures::channel::oneshot;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, Runtime};
+use tracing::{error, info};
+use zbus::{
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue},
+    Connection, DBusError, Proxy,
+};
+
+use crate::desktop::{object_remover, BluetoothManager, ObjectRemover};
+use crate::events::{emit_change, BluetoothEvent};
+use crate::models::{IncomingTransfer, ObexReceiveConfig};
+use crate::obex::{map_obex_error, transfer_from_props, watch_transfer, OBEX_PATH, OBEX_SERVICE};
+use crate::{Error, Result};
+
+pub const OBEX_AGENT_PATH: &str = "/org/vasak/bluetooth/obex_agent";
+
+macro_rules! get_prop {
+    ($props:expr, $key:expr, $ty:ty) => {
+        $props.get($key).and_then(|v| <$ty>::try_from(&**v).ok())
+    };
+}
+
+enum Decision {
+    Accept(Option<String>),
+    Reject,
+}
+
+type PendingMap = Arc<Mutex<HashMap<String, oneshot::Sender<Decision>>>>;
+
+/// Incoming OBEX state kept inside `BluetoothManager`.
+#[derive(Default)]
+pub struct ObexAgentState {
+    pending: PendingMap,
+    config: Arc<Mutex<ObexReceiveConfig>>,
+    /// Held across (un)registration so concurrent calls don't both act.
+    registration: tokio::sync::Mutex<Option<(Connection, ObjectRemover)>>,
+}
+
+#[derive(DBusError, Debug)]
+#[zbus(prefix = "org.bluez.obex.Error")]
+enum AgentError {
+    #[zbus(error)]
+    ZBus(zbus::Error),
+    Rejected(String),
+    Canceled(String),
+}
+
+// The connection comes with each call: holding it here would keep it alive through the
+// object server that holds the agent
+struct ObexAgent<R: Runtime> {
+    app: AppHandle<R>,
+    pending: PendingMap,
+    config: Arc<Mutex<ObexReceiveConfig>>,
+}
+
+impl<R: Runtime> ObexAgent<R> {
+    fn emit(&self, change_type: &str, data: serde_json::Value) {
+        emit_change(&self.app, BluetoothEvent::other(change_type, data));
+    }
+
+    async fn describe(&self, conn: &Connection, transfer: &ObjectPath<'_>) -> zbus::Result<IncomingTransfer> {
+        let proxy = Proxy::new(
+            conn,
+            OBEX_SERVICE,
+            transfer.as_str(),
+            "org.freedesktop.DBus.Properties",
+        )
+        .await?;
+        let reply = proxy.call_method("GetAll", &("org.bluez.obex.Transfer1",)).await?;
+        let props: HashMap<String, OwnedValue> = reply.body().deserialize()?;
+
+        let session = props
+            .get("Session")
+            .and_then(|v| ObjectPath::try_from(&**v).ok())
+            .map(|p: ObjectPath| p.to_string());
+
+        let mut device_address = None;
+        if let Some(session) = session {
+            let session_proxy = Proxy::new(
+                conn,
+                OBEX_SERVICE,
+                session.as_str(),
+                "org.freedesktop.DBus.Properties",
+            )
+            .await?;
+            if let Ok(reply) = session_proxy
+                .call_method("GetAll", &("org.bluez.obex.Session1",))
+                .await
+            {
+                let session_props: HashMap<String, OwnedValue> = reply.body().deserialize()?;
+                device_address = get_prop!(session_props, "Destination", String);
+            }
+        }
+
+        Ok(IncomingTransfer {
+            path: transfer.to_string(),
+            device_address,
+            name: get_prop!(props, "Name", String),
+            // obexd reports 0 when the sender did not announce a length
+            size: get_prop!(props, "Size", u64).filter(|s| *s > 0),
+        })
+    }
+}
+
+#[zbus::interface(name = "org.bluez.obex.Agent1")]
+impl<R: Runtime> ObexAgent<R> {
+    async fn release(&self) {
+        info!("OBEX agent released by obexd");
+    }
+
+    async fn authorize_push(
+        &self,
+        #[zbus(connection)] conn: &Connection,
+        transfer: OwnedObjectPath,
+    ) -> std::result::Result<String, AgentError> {
+        let incoming = self.describe(conn, &transfer).await?;
+        info!("Incoming OBEX push: {:?}", incoming);
+
+        let max_file_size = self.config.lock().unwrap().max_file_size;
+        if let Some(max) = max_file_size {
+            // A push without an announced length could grow past the limit unchecked
+            let reason = match incoming.size {
+                Some(size) if size > max => {
+                    Some(format!("File exceeds the size limit ({} > {} bytes)", size, max))
+                }
+                Some(_) => None,
+                None => Some(format!("File size unknown with a size limit of {} bytes", max)),
+            };
+            if let Some(reason) = reason {
+                self.emit("obex-incoming-rejected", serde_json::json!({
+                    "path": incoming.path,
+                    "reason": reason,
+                }));
+                return Err(AgentError::Rejected(reason));
+            }
+        }
+
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(incoming.path.clone(), tx);
+        self.emit(
+            "obex-incoming-request",
+            serde_json::to_value(&incoming).unwrap_or_default(),
+        );
+
+        let target_dir = match rx.await {
+            Ok(Decision::Accept(target_dir)) => target_dir,
+            Ok(Decision::Reject) => {
+                self.emit("obex-incoming-rejected", serde_json::json!({
+                    "path": incoming.path,
+                    "reason": "Rejected by user",
+                }));
+                return Err(AgentError::Rejected("Rejected by user".to_string()));
+            }
+            Err(_) => {
+                self.emit("obex-incoming-rejected", serde_json::json!({
+                    "path": incoming.path,
+                    "reason": "Canceled by sender",
+                }));
+                return Err(AgentError::Canceled("Request canceled".to_string()));
+            }
+        };
+
+        let target_dir = target_dir
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(&self.config.lock().unwrap().download_dir));
+        let target = unique_target_path(&target_dir, incoming.name.as_deref())
+            .map_err(|e| AgentError::Rejected(e.to_string()))?;
+        let target = target.to_string_lossy().to_string();
+        info!("Accepted incoming OBEX push into {}", target);
+
+        let mut transfer_info = transfer_from_props(incoming.path.clone(), &HashMap::new());
+        transfer_info.name = incoming.name.clone();
+        transfer_info.size = incoming.size;
+        transfer_info.filename = Some(target.clone());
+        if let Some(manager) = self.app.try_state::<BluetoothManager>() {
+            manager.obex_transfers.insert(&transfer_info.path, conn);
+        }
+        tauri::async_runtime::spawn(watch_transfer(
+            conn.clone(),
+            self.app.clone(),
+            transfer_info,
+            None,
+        ));
+
+        Ok(target)
+    }
+
+    async fn cancel(&self) {
+        // Dropping the senders makes every waiting AuthorizePush return Canceled
+        let dropped = self.pending.lock().unwrap().drain().count();
+        info!("obexd canceled {} pending OBEX request(s)", dropped);
+    }
+}
+
+/// Builds `<dir>/<name>`, appending " (n)" before the extension if the file already exists.
+fn unique_target_path(dir: &Path, name: Option<&str>) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+
+    // Never trust the remote name to stay inside the target directory
+    let file_name = name
+        .and_then(|n| Path::new(n).file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| "received_file".to_string());
+
+    let candidate = dir.join(&file_name);
+    if !candidate.exists() {
+        return Ok(candidate);
+    }
+
+    let stem = Path::new(&file_name)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| file_name.clone());
+    let extension = Path::new(&file_name)
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+
+    let mut n = 1;
+    loop {
+        let candidate = dir.join(format!("{} ({}){}", stem, n, extension));
+        if !candidate.exists() {
+            return Ok(candidate);
+        }
+        n += 1;
+    }
+}
+
+impl ObexAgentState {
+    /// Exports the agent on the session bus and registers it with `AgentManager1`.
+    pub async fn register<R: Runtime>(&self, app: AppHandle<R>) -> Result<()> {
+        let mut registration = self.registration.lock().await;
+        if registration.is_some() {
+            info!("OBEX agent already registered");
+            return Ok(());
+        }
+
+        let conn = Connection::session().await?;
+        let agent = ObexAgent {
+            app,
+            pending: self.pending.clone(),
+            config: self.config.clone(),
+        };
+        conn.object_server().at(OBEX_AGENT_PATH, agent).await?;
+        let remove = object_remover::<ObexAgent<R>>();
+
+        let proxy = Proxy::new(&conn, OBEX_SERVICE, OBEX_PATH, "org.bluez.obex.AgentManager1").await?;
+        if let Err(e) = proxy
+            .call_method("RegisterAgent", &(ObjectPath::try_from(OBEX_AGENT_PATH)?,))
+            .await
+        {
+            error!("Error registering OBEX agent: {}", e);
+            let _ = remove(conn, OBEX_AGENT_PATH.to_string()).await;
+            return Err(map_obex_error(e));
+        }
+
+        info!("OBEX agent registered at {}", OBEX_AGENT_PATH);
+        *registration = Some((conn, remove));
+        Ok(())
+    }
+
+    pub async fn unregister(&self) -> Result<()> {
+        let mut registration = self.registration.lock().await;
+        let Some((conn, remove)) = registration.take() else {
+            return Ok(());
+        };
+        self.pending.lock().unwrap().clear();
+
+        let proxy = Proxy::new(&conn, OBEX_SERVICE, OBEX_PATH, "org.bluez.obex.AgentManager1").await?;
+        if let Err(e) = proxy
+            .call_method("UnregisterAgent", &(ObjectPath::try_from(OBEX_AGENT_PATH)?,))
+            .await
+        {
+            // obexd may already be gone; the agent is removed from the bus either way
+            error!("Error unregistering OBEX agent: {}", e);
+        }
+        remove(conn, OBEX_AGENT_PATH.to_string()).await?;
+        Ok(())
+    }
+
+    pub fn accept(&self, transfer_path: &str, target_dir: Option<String>) -> Result<()> {
+        self.resolve(transfer_path, Decision::Accept(target_dir))
+    }
+
+    pub fn reject(&self, transfer_path: &str) -> Result<()> {
+        self.resolve(transfer_path, Decision::Reject)
+    }
+
+    fn resolve(&self, transfer_path: &str, decision: Decision) -> Result<()> {
+        let sender = self
+            .pending
+            .lock()
+            .unwrap()
+            .remove(transfer_path)
+            .ok_or_else(|| Error::NotFound(format!("No pending transfer: {}", transfer_path)))?;
+        sender
+            .send(decision)
+            .map_err(|_| Error::CommandError("Transfer request is no longer pending".to_string()))
+    }
+
+    pub fn config(&self) -> ObexReceiveConfig {
+        self.config.lock().unwrap().clone()
+    }
+
+    pub fn set_config(&self, config: ObexReceiveConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime};
+use tracing::{error, info};
+use zbus::{
+    zvariant::{OwnedObjectPath, OwnedValue},
+    Connection, Proxy,
+};
+
+use crate::desktop::BluetoothManager;
+use crate::events::{emit_change, BluetoothEvent};
+use crate::models::{FtpEntry, ObexTransfer};
+use crate::obex::{
+    create_session, map_obex_error, remove_session, transfer_from_props, watch_transfer,
+    OBEX_SERVICE,
+};
+use crate::{Error, Result};
+
+/// Sessions unused for this long are closed by the reaper.
+const FTP_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+const FTP_REAPER_INTERVAL: Duration = Duration::from_secs(30);
+
+macro_rules! get_prop {
+    ($props:expr, $key:expr, $ty:ty) => {
+        $props.get($key).and_then(|v| <$ty>::try_from(&**v).ok())
+    };
+    ($props:expr, $key:expr, $ty:ty, $default:expr) => {
+        $props.get($key).and_then(|v| <$ty>::try_from(&**v).ok()).unwrap_or($default)
+    };
+}
+
+struct FtpSession {
+    conn: Connection,
+    path: OwnedObjectPath,
+    last_used: Instant,
+    // Running GetFile/PutFile transfers keep the session alive
+    active_transfers: Arc<AtomicUsize>,
+}
+
+/// OBEX FTP sessions kept inside `BluetoothManager`, keyed by session object path.
+#[derive(Default)]
+pub struct FtpSessions {
+    sessions: Arc<Mutex<HashMap<String, FtpSession>>>,
+    reaper_started: AtomicBool,
+}
+
+/// Converts OBEX timestamps ("20240131T235959Z") into ISO 8601 ("2024-01-31T23:59:59Z").
+fn parse_obex_time(value: &str) -> String {
+    let bytes = value.as_bytes();
+    if bytes.len() >= 15 && bytes[..15].is_ascii() && bytes[8] == b'T' {
+        format!(
+            "{}-{}-{}T{}:{}:{}{}",
+            &value[0..4],
+            &value[4..6],
+            &value[6..8],
+            &value[9..11],
+            &value[11..13],
+            &value[13..15],
+            &value[15..]
+        )
+    } else {
+        value.to_string()
+    }
+}
+
+fn entry_from_props(props: &HashMap<String, OwnedValue>) -> FtpEntry {
+    FtpEntry {
+        name: get_prop!(props, "Name", String, String::new()),
+        entry_type: get_prop!(props, "Type", String, "file".to_string()),
+        size: get_prop!(props, "Size", u64),
+        modified: get_prop!(props, "Modified", String).map(|m| parse_obex_time(&m)),
+    }
+}
+
+/// Absolute path for a download. obexd resolves relative paths against its own working
+/// directory, so the folder must exist and is canonicalized here.
+fn download_target(local_path: &str) -> Result<PathBuf> {
+    let path = Path::new(local_path);
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| Error::CommandError(format!("Not a file path: {}", local_path)))?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let dir = std::fs::canonicalize(dir)
+        .map_err(|e| Error::NotFound(format!("Folder not found: {} ({})", dir.display(), e)))?;
+    Ok(dir.join(file_name))
+}
+
+impl FtpSessions {
+    /// Returns the session connection and refreshes its idle timer.
+    fn touch(&self, session: &str) -> Result<(Connection, OwnedObjectPath, Arc<AtomicUsize>)> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let entry = sessions
+            .get_mut(session)
+            .ok_or_else(|| Error::NotFound(format!("No FTP session: {}", session)))?;
+        entry.last_used = Instant::now();
+        Ok((entry.conn.clone(), entry.path.clone(), entry.active_transfers.clone()))
+    }
+
+    async fn proxy(&self, session: &str) -> Result<(Connection, Proxy<'static>)> {
+        let (conn, path, _) = self.touch(session)?;
+        let proxy = Proxy::new(
+            &conn,
+            OBEX_SERVICE,
+            path,
+            "org.bluez.obex.FileTransfer1",
+        )
+        .await?;
+        Ok((conn, proxy))
+    }
+
+    /// Opens an FTP session with the device and returns its session path.
+    pub async fn connect<R: Runtime>(&self, app: AppHandle<R>, device_address: &str) -> Result<String> {
+        let conn = Connection::session().await?;
+        let path = create_session(&conn, device_address, "ftp").await?;
+        let id = path.to_string();
+
+        self.sessions.lock().unwrap().insert(
+            id.clone(),
+            FtpSession {
+                conn,
+                path,
+                last_used: Instant::now(),
+                active_transfers: Arc::new(AtomicUsize::new(0)),
+            },
+        );
+
+        if !self.reaper_started.swap(true, Ordering::SeqCst) {
+            tauri::async_runtime::spawn(run_idle_reaper(self.sessions.clone(), app));
+        }
+
+        Ok(id)
+    }
+
+    pub async fn disconnect(&self, session: &str) -> Result<()> {
+        let Some(entry) = self.sessions.lock().unwrap().remove(session) else {
+            return Ok(());
+        };
+        remove_session(&entry.conn, &entry.path).await
+    }
+
+    pub async fn list_folder(&self, session: &str) -> Result<Vec<FtpEntry>> {
+        let (_, proxy) = self.proxy(session).await?;
+        let reply = proxy
+            .call_method("ListFolder", &())
+            .await
+            .map_err(map_obex_error)?;
+        let entries: Vec<HashMap<String, OwnedValue>> = reply.body().deserialize()?;
+        Ok(entries.iter().map(entry_from_props).collect())
+    }
+
+    pub async fn change_folder(&self, session: &str, folder: &str) -> Result<()> {
+        let (_, proxy) = self.proxy(session).await?;
+        proxy
+            .call_method("ChangeFolder", &(folder,))
+            .await
+            .map_err(map_obex_error)?;
+        Ok(())
+    }
+
+    pub async fn create_folder(&self, session: &str, folder: &str) -> Result<()> {
+        let (_, proxy) = self.proxy(session).await?;
+        proxy
+            .call_method("CreateFolder", &(folder,))
+            .await
+            .map_err(map_obex_error)?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, session: &str, name: &str) -> Result<()> {
+        let (_, proxy) = self.proxy(session).await?;
+        proxy
+            .call_method("Delete", &(name,))
+            .await
+            .map_err(map_obex_error)?;
+        Ok(())
+    }
+
+    /// Downloads `remote_name` from the current folder into `local_path`.
+    pub async fn get_file<R: Runtime>(
+        &self,
+        app: AppHandle<R>,
+        session: &str,
+        remote_name: &str,
+        local_path: &str,
+    ) -> Result<ObexTransfer> {
+        let target = download_target(local_path)?;
+        let (conn, proxy) = self.proxy(session).await?;
+        let reply = proxy
+            .call_method("GetFile", &(target.to_string_lossy().as_ref(), remote_name))
+            .await
+            .map_err(map_obex_error)?;
+        self.track(session, conn, app, reply)
+    }
+
+    /// Uploads `local_path` into the current folder as `remote_name`.
+    pub async fn put_file<R: Runtime>(
+        &self,
+        app: AppHandle<R>,
+        session: &str,
+        local_path: &str,
+        remote_name: &str,
+    ) -> Result<ObexTransfer> {
+        let source = Path::new(local_path);
+        if !source.is_file() {
+            return Err(Error::NotFound(format!("File not found: {}", local_path)));
+        }
+        let source = std::fs::canonicalize(source)
+            .map_err(|e| Error::NotFound(format!("File not found: {} ({})", local_path, e)))?;
+        let (conn, proxy) = self.proxy(session).await?;
+        let reply = proxy
+            .call_method("PutFile", &(source.to_string_lossy().as_ref(), remote_name))
+            .await
+            .map_err(map_obex_error)?;
+        self.track(session, conn, app, reply)
+    }
+
+    fn track<R: Runtime>(
+        &self,
+        session: &str,
+        conn: Connection,
+        app: AppHandle<R>,
+        reply: zbus::Message,
+    ) -> Result<ObexTransfer> {
+        let (transfer_path, props): (OwnedObjectPath, HashMap<String, OwnedValue>) =
+            reply.body().deserialize()?;
+        let transfer = transfer_from_props(transfer_path.to_string(), &props);
+
+        let (_, _, active_transfers) = self.touch(session)?;
+        active_transfers.fetch_add(1, Ordering::SeqCst);
+        let sessions = self.sessions.clone();
+        let session = session.to_string();
+        let watched = transfer.clone();
+        if let Some(manager) = app.try_state::<BluetoothManager>() {
+            manager.obex_transfers.insert(&transfer.path, &conn);
+        }
+        tauri::async_runtime::spawn(async move {
+            watch_transfer(conn, app, watched, None).await;
+            active_transfers.fetch_sub(1, Ordering::SeqCst);
+            if let Some(entry) = sessions.lock().unwrap().get_mut(&session) {
+                entry.last_used = Instant::now();
+            }
+        });
+        Ok(transfer)
+    }
+}
+
+async fn run_idle_reaper<R: Runtime>(
+    sessions: Arc<Mutex<HashMap<String, FtpSession>>>,
+    app: AppHandle<R>,
+) {
+    loop {
+        tokio::time::sleep(FTP_REAPER_INTERVAL).await;
+
+        let expired: Vec<(String, FtpSession)> = {
+            let mut sessions = sessions.lock().unwrap();
+            let ids: Vec<String> = sessions
+                .iter()
+                .filter(|(_, s)| {
+                    s.active_transfers.load(Ordering::SeqCst) == 0
+                        && s.last_used.elapsed() >= FTP_IDLE_TIMEOUT
+                })
+                .map(|(id, _)| id.clone())
+                .collect();
+            ids.into_iter()
+                .filter_map(|id| sessions.remove(&id).map(|s| (id, s)))
+                .collect()
+        };
+
+        for (id, session) in expired {
+            info!("Closing idle FTP session {}", id);
+            if let Err(e) = remove_session(&session.conn, &session.path).await {
+                error!("Error closing idle FTP session {}: {:?}", id, e);
+            }
+            emit_change(&app, BluetoothEvent::other("obex-session-closed", serde_json::json!({ "session": id, "reason": "idle" })));
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use zbus::{
+    zvariant::{OwnedObjectPath, OwnedValue, Value as ZbusValue},
+    Connection, Proxy,
+};
+
+use crate::desktop::{helper_adapter_info_from_props, helper_device_info_from_props};
+use crate::models::{AdapterInfo, DeviceInfo};
+use crate::Result;
+
+/// Interfaces whose properties are mirrored.
+const CACHED_INTERFACES: [&str; 3] = ["org.bluez.Adapter1", "org.bluez.Device1", "org.bluez.Input1"];
+
+type Interfaces = HashMap<String, HashMap<String, OwnedValue>>;
+
+/// Local mirror of the BlueZ adapter and device properties, kept up to date from
+/// `InterfacesAdded`, `InterfacesRemoved` and `PropertiesChanged`, so signals can be turned
+/// into full snapshots without a `GetAll` round trip.
+#[derive(Default)]
+pub struct ObjectCache {
+    objects: Mutex<HashMap<String, Interfaces>>,
+}
+
+fn cached(interfaces: Interfaces) -> Interfaces {
+    interfaces
+        .into_iter()
+        .filter(|(name, _)| CACHED_INTERFACES.contains(&name.as_str()))
+        .collect()
+}
+
+impl ObjectCache {
+    /// Fills the cache from `GetManagedObjects`.
+    pub async fn load(&self, conn: &Connection) -> Result<()> {
+        let proxy = Proxy::new(conn, "org.bluez", "/", "org.freedesktop.DBus.ObjectManager").await?;
+        let reply = proxy.call_method("GetManagedObjects", &()).await?;
+        let objects: HashMap<OwnedObjectPath, Interfaces> = reply.body().deserialize()?;
+
+        let mut cache = self.objects.lock().unwrap();
+        cache.clear();
+        for (path, interfaces) in objects {
+            let interfaces = cached(interfaces);
+            if !interfaces.is_empty() {
+                cache.insert(path.to_string(), interfaces);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn add(&self, path: &str, interfaces: &Interfaces) {
+        let interfaces: Interfaces = interfaces
+            .iter()
+            .filter(|(name, _)| CACHED_INTERFACES.contains(&name.as_str()))
+            .map(|(name, props)| {
+                let props = props
+                    .iter()
+                    .filter_map(|(k, v)| v.try_clone().ok().map(|v| (k.clone(), v)))
+                    .collect();
+                (name.clone(), props)
+            })
+            .collect();
+        if interfaces.is_empty() {
+            return;
+        }
+        self.objects
+            .lock()
+            .unwrap()
+            .entry(path.to_string())
+            .or_default()
+            .extend(interfaces);
+    }
+
+    pub fn remove(&self, path: &str, interfaces: &[String]) {
+        let mut objects = self.objects.lock().unwrap();
+        if let Some(cached) = objects.get_mut(path) {
+            for interface in interfaces {
+                cached.remove(interface);
+            }
+            if cached.is_empty() {
+                objects.remove(path);
+            }
+        }
+    }
+
+    /// Applies a `PropertiesChanged`: changed values are merged, invalidated keys dropped.
+    pub fn update(
+        &self,
+        path: &str,
+        interface: &str,
+        changed: &HashMap<String, ZbusValue<'_>>,
+        invalidated: &[String],
+    ) {
+        if !CACHED_INTERFACES.contains(&interface) {
+            return;
+        }
+        let mut objects = self.objects.lock().unwrap();
+        let props = objects
+            .entry(path.to_string())
+            .or_default()
+            .entry(interface.to_string())
+            .or_default();
+        for (key, value) in changed {
+            if let Ok(value) = value.try_to_owned() {
+                props.insert(key.clone(), value);
+            }
+        }
+        for key in invalidated {
+            props.remove(key);
+        }
+    }
+
+    pub fn adapter(&self, path: &str) -> Option<AdapterInfo> {
+        let objects = self.objects.lock().unwrap();
+        let props = objects.get(path)?.get("org.bluez.Adapter1")?;
+        if !props.contains_key("Address") {
+            return None;
+        }
+        Some(helper_adapter_info_from_props(path.to_string(), props))
+    }
+
+    /// Snapshots of the devices known to an adapter.
+    pub fn devices(&self, adapter_path: &str) -> Vec<DeviceInfo> {
+        let prefix = format!("{}/", adapter_path);
+        let paths: Vec<String> = self
+            .objects
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|path| path.starts_with(&prefix))
+            .cloned()
+            .collect();
+        let mut devices: Vec<DeviceInfo> = paths.iter().filter_map(|path| self.device(path)).collect();
+        devices.sort_by(|a, b| a.path.cmp(&b.path));
+        devices
+    }
+
+    /// Snapshot of a device; `None` until its `Device1` interface has been seen.
+    pub fn device(&self, path: &str) -> Option<DeviceInfo> {
+        let objects = self.objects.lock().unwrap();
+        let interfaces = objects.get(path)?;
+        let props = interfaces.get("org.bluez.Device1")?;
+        // An entry created by a PropertiesChanged alone lacks the address
+        if !props.contains_key("Address") {
+            return None;
+        }
+        Some(helper_device_info_from_props(
+            path.to_string(),
+            props,
+            interfaces.get("org.bluez.Input1"),
+        ))
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use tauri::{AppHandle, Runtime};
+use tracing::{error, info};
+use zbus::{
+    zvariant::{OwnedObjectPath, OwnedValue, Value as ZbusValue},
+    Connection, Proxy,
+};
+
+use crate::models::{PhonebookEntry, VCardContact};
+use crate::obex::{
+    create_session, map_obex_error, private_temp_path, remove_session, transfer_from_props,
+    wait_transfer, OBEX_SERVICE,
+};
+use crate::vcard::parse_vcards;
+use crate::{Error, Result};
+
+const PHONEBOOKS: &[&str] = &["pb", "ich", "och", "mch", "cch", "spd", "fav"];
+
+/// Maps user-facing repository names to the ones `PhonebookAccess1.Select` expects.
+fn normalize_location(location: &str) -> Result<&'static str> {
+    match location.to_ascii_lowercase().as_str() {
+        "int" | "internal" | "phone" => Ok("int"),
+        "sim" | "sim1" => Ok("sim1"),
+        other => Err(Error::CommandError(format!(
+            "Unknown phonebook repository: {} (expected \"internal\" or \"sim\")",
+            other
+        ))),
+    }
+}
+
+fn normalize_phonebook(phonebook: &str) -> Result<String> {
+    let phonebook = phonebook.to_ascii_lowercase();
+    if PHONEBOOKS.contains(&phonebook.as_str()) {
+        Ok(phonebook)
+    } else {
+        Err(Error::CommandError(format!(
+            "Unknown phonebook: {} (expected one of {})",
+            phonebook,
+            PHONEBOOKS.join(", ")
+        )))
+    }
+}
+
+fn filters(offset: Option<u16>, max_count: Option<u16>) -> HashMap<&'static str, ZbusValue<'static>> {
+    let mut filters: HashMap<&str, ZbusValue> = HashMap::new();
+    filters.insert("Format", ZbusValue::from("vcard30"));
+    if let Some(offset) = offset {
+        filters.insert("Offset", ZbusValue::from(offset));
+    }
+    if let Some(max_count) = max_count {
+        filters.insert("MaxCount", ZbusValue::from(max_count));
+    }
+    filters
+}
+
+/// A PBAP session with the repository and phonebook already selected.
+struct PbapSession {
+    conn: Connection,
+    path: OwnedObjectPath,
+    proxy: Proxy<'static>,
+}
+
+impl PbapSession {
+    async fn open(device_address: &str, location: &str, phonebook: &str) -> Result<Self> {
+        let location = normalize_location(location)?;
+        let phonebook = normalize_phonebook(phonebook)?;
+
+        let conn = Connection::session().await?;
+        let path = create_session(&conn, device_address, "pbap").await?;
+        let proxy = Proxy::new(
+            &conn,
+            OBEX_SERVICE,
+            path.clone(),
+            "org.bluez.obex.PhonebookAccess1",
+        )
+        .await?;
+
+        let session = Self { conn, path, proxy };
+        if let Err(e) = session
+            .proxy
+            .call_method("Select", &(location, phonebook.as_str()))
+            .await
+        {
+            error!("PBAP Select({}, {}) error: {}", location, phonebook, e);
+            session.close().await;
+            return Err(map_obex_error(e));
+        }
+        Ok(session)
+    }
+
+    async fn close(self) {
+        if let Err(e) = remove_session(&self.conn, &self.path).await {
+            error!("Error closing PBAP session {}: {:?}", self.path.as_str(), e);
+        }
+    }
+
+    async fn list(&self, offset: Option<u16>, max_count: Option<u16>) -> Result<Vec<PhonebookEntry>> {
+        let reply = self
+            .proxy
+            .call_method("List", &(filters(offset, max_count),))
+            .await
+            .map_err(map_obex_error)?;
+        let entries: Vec<(String, String)> = reply.body().deserialize()?;
+        Ok(entries
+            .into_iter()
+            .map(|(handle, name)| PhonebookEntry { handle, name })
+            .collect())
+    }
+
+    /// Runs a pull method into `target` (a `private_temp_path`) and parses the resulting
+    /// vCards. The file is removed however the pull ends.
+    async fn pull_into_contacts<B>(&self, method: &str, target: &str, body: B) -> Result<Vec<VCardContact>>
+    where
+        B: serde::Serialize + zbus::zvariant::DynamicType,
+    {
+        let data = self.pull_into_file(method, body).await.and_then(|filename| {
+            let data = std::fs::read(&filename)
+                .map_err(|e| Error::CommandError(format!("Error reading {}: {}", filename, e)));
+            let _ = std::fs::remove_file(&filename);
+            data
+        });
+        let _ = std::fs::remove_file(target);
+        Ok(parse_vcards(&String::from_utf8_lossy(&data?)))
+    }
+
+    async fn pull_into_file<B>(&self, method: &str, body: B) -> Result<String>
+    where
+        B: serde::Serialize + zbus::zvariant::DynamicType,
+    {
+        let reply = self
+            .proxy
+            .call_method(method, &body)
+            .await
+            .map_err(map_obex_error)?;
+        let (transfer_path, props): (OwnedObjectPath, HashMap<String, OwnedValue>) =
+            reply.body().deserialize()?;
+        let transfer = transfer_from_props(transfer_path.to_string(), &props);
+        let transfer = wait_transfer(&self.conn, transfer).await?;
+        transfer
+            .filename
+            .ok_or_else(|| Error::CommandError("PBAP transfer has no target file".to_string()))
+    }
+}
+
+/// Lists the vCard handles and names of a phonebook (`PhonebookAccess1.List`).
+pub async fn list(
+    device_address: &str,
+    location: &str,
+    phonebook: &str,
+    offset: Option<u16>,
+    max_count: Option<u16>,
+) -> Result<Vec<PhonebookEntry>> {
+    let session = PbapSession::open(device_address, location, phonebook).await?;
+    let result = session.list(offset, max_count).await;
+    session.close().await;
+    result
+}
+
+/// Pulls a whole phonebook (`PhonebookAccess1.PullAll`) and parses it into contacts.
+pub async fn pull_all<R: Runtime>(
+    app: &AppHandle<R>,
+    device_address: &str,
+    location: &str,
+    phonebook: &str,
+    offset: Option<u16>,
+    max_count: Option<u16>,
+) -> Result<Vec<VCardContact>> {
+    let target = private_temp_path(app, "pbap", "vcf")?;
+    let session = PbapSession::open(device_address, location, phonebook).await?;
+    info!("Pulling PBAP {}/{} from {} into {}", location, phonebook, device_address, target);
+    let result = session
+        .pull_into_contacts("PullAll", &target, (target.as_str(), filters(offset, max_count)))
+        .await;
+    session.close().await;
+    result
+}
+
+/// Pulls a single vCard by handle (`PhonebookAccess1.Pull`).
+pub async fn pull<R: Runtime>(
+    app: &AppHandle<R>,
+    device_address: &str,
+    location: &str,
+    phonebook: &str,
+    handle: &str,
+) -> Result<VCardContact> {
+    let target = private_temp_path(app, "pbap", "vcf")?;
+    let session = PbapSession::open(device_address, location, phonebook).await?;
+    let result = session
+        .pull_into_contacts("Pull", &target, (handle, target.as_str(), filters(None, None)))
+        .await;
+    session.close().await;
+    result?
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::NotFound(format!("vCard not found: {}", handle)))
+}
@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::desktop::BluetoothManager;
+use crate::device_lookup::{address_from_path, normalize_address};
+use crate::events::{emit_change, BluetoothEvent};
+use crate::models::{PresenceConfig, ProximityInfo};
+use crate::{Error, Result};
+
+const TICK_INTERVAL: Duration = Duration::from_secs(2);
+/// Gone devices not seen for this long are forgotten, so a busy scan doesn't pile them up.
+const GONE_TTL: Duration = Duration::from_secs(10 * 60);
+/// Typical gap between the advertised TX power (at 0 m) and the RSSI measured at 1 m.
+const TX_POWER_TO_1M: f64 = 41.0;
+/// RSSI at 1 m assumed when the device doesn't advertise TX power.
+const DEFAULT_RSSI_1M: f64 = -59.0;
+/// Path-loss exponent: 2 in free space, 2.5-4 indoors.
+const PATH_LOSS_EXPONENT: f64 = 2.5;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Proximity {
+    Near,
+    Far,
+    Gone,
+}
+
+impl Proximity {
+    fn as_str(self) -> &'static str {
+        match self {
+            Proximity::Near => "near",
+            Proximity::Far => "far",
+            Proximity::Gone => "gone",
+        }
+    }
+}
+
+struct Tracker {
+    path: String,
+    smoothed: Option<f64>,
+    raw: Option<i16>,
+    tx_power: Option<i16>,
+    connected: bool,
+    last_seen: Instant,
+    state: Proximity,
+}
+
+impl Tracker {
+    fn distance(&self) -> Option<f64> {
+        let rssi = self.smoothed?;
+        let rssi_1m = self
+            .tx_power
+            .map(|tx| tx as f64 - TX_POWER_TO_1M)
+            .unwrap_or(DEFAULT_RSSI_1M);
+        let meters = 10f64.powf((rssi_1m - rssi) / (10.0 * PATH_LOSS_EXPONENT));
+        Some((meters * 10.0).round() / 10.0)
+    }
+
+    fn info(&self, address: &str) -> ProximityInfo {
+        ProximityInfo {
+            address: address.to_string(),
+            path: self.path.clone(),
+            state: self.state.as_str().to_string(),
+            rssi: self.smoothed.map(|r| (r * 10.0).round() / 10.0),
+            raw_rssi: self.raw,
+            distance: self.distance(),
+            connected: self.connected,
+        }
+    }
+}
+
+/// What the listener saw for a device in one signal.
+#[derive(Default)]
+pub struct Observation {
+    pub rssi: Option<i16>,
+    pub tx_power: Option<i16>,
+    pub connected: Option<bool>,
+}
+
+/// Presence engine: smooths RSSI with an exponential moving average, estimates distance
+/// from TX power and classifies devices as near, far or gone.
+///
+/// Leaving `near` takes `hysteresis` dB below `near_rssi`, so a device sitting on the
+/// threshold doesn't flap. `gone` means no RSSI for `gone_timeout_ms` while disconnected;
+/// RSSI only arrives while discovering, so presence needs an active scan for devices that
+/// aren't connected.
+#[derive(Default)]
+pub struct PresenceEngine {
+    trackers: Mutex<HashMap<String, Tracker>>,
+    config: Mutex<PresenceConfig>,
+}
+
+fn emit_proximity<R: Runtime>(app: &AppHandle<R>, info: ProximityInfo, previous: Proximity) {
+    let mut data = serde_json::to_value(info).unwrap_or_default();
+    data["previous"] = serde_json::json!(previous.as_str());
+    emit_change(app, BluetoothEvent::other("device-proximity-changed", data));
+}
+
+impl PresenceEngine {
+    pub fn config(&self) -> PresenceConfig {
+        self.config.lock().unwrap().clone()
+    }
+
+    pub fn set_config(&self, config: PresenceConfig) -> Result<()> {
+        if config.hysteresis <= 0 {
+            return Err(Error::CommandError(format!(
+                "hysteresis must be positive so the far threshold stays below nearRssi (got {})",
+                config.hysteresis
+            )));
+        }
+        if config.gone_timeout_ms == 0 {
+            return Err(Error::CommandError("goneTimeoutMs must be greater than 0".to_string()));
+        }
+        if !(config.smoothing > 0.0 && config.smoothing <= 1.0) {
+            return Err(Error::CommandError(format!(
+                "smoothing must be in (0, 1] (got {})",
+                config.smoothing
+            )));
+        }
+        *self.config.lock().unwrap() = config;
+        Ok(())
+    }
+
+    pub fn observe<R: Runtime>(&self, app: &AppHandle<R>, device_path: &str, observation: Observation) {
+        let Some(address) = address_from_path(device_path) else {
+            return;
+        };
+        if observation.rssi.is_none() && observation.tx_power.is_none() && observation.connected.is_none() {
+            return;
+        }
+        let config = self.config();
+
+        let changed = {
+            let mut trackers = self.trackers.lock().unwrap();
+            let tracker = trackers.entry(address.clone()).or_insert_with(|| Tracker {
+                path: device_path.to_string(),
+                smoothed: None,
+                raw: None,
+                tx_power: None,
+                connected: false,
+                last_seen: Instant::now(),
+                state: Proximity::Gone,
+            });
+            tracker.path = device_path.to_string();
+            if observation.tx_power.is_some() {
+                tracker.tx_power = observation.tx_power;
+            }
+            if let Some(connected) = observation.connected {
+                tracker.connected = connected;
+                if connected {
+                    tracker.last_seen = Instant::now();
+                }
+            }
+
+            let previous = tracker.state;
+            if let Some(rssi) = observation.rssi {
+                let alpha = config.smoothing.clamp(0.01, 1.0);
+                // Start over after a gone period instead of averaging with stale samples
+                let smoothed = match tracker.smoothed.filter(|_| previous != Proximity::Gone) {
+                    Some(smoothed) => alpha * rssi as f64 + (1.0 - alpha) * smoothed,
+                    None => rssi as f64,
+                };
+                tracker.smoothed = Some(smoothed);
+                tracker.raw = Some(rssi);
+                tracker.last_seen = Instant::now();
+
+                let near = config.near_rssi as f64;
+                tracker.state = match previous {
+                    Proximity::Near if smoothed < near - config.hysteresis as f64 => Proximity::Far,
+                    Proximity::Near => Proximity::Near,
+                    _ if smoothed >= near => Proximity::Near,
+                    _ => Proximity::Far,
+                };
+            } else if tracker.connected && previous == Proximity::Gone {
+                // Connected without RSSI (classic links): around, distance unknown
+                tracker.state = Proximity::Far;
+            }
+
+            (tracker.state != previous).then(|| (tracker.info(&address), previous))
+        };
+
+        if let Some((info, previous)) = changed {
+            emit_proximity(app, info, previous);
+        }
+    }
+
+    /// BlueZ dropped the device object or a monitor lost it: it is gone right away.
+    pub fn remove<R: Runtime>(&self, app: &AppHandle<R>, device_path: &str) {
+        let Some(address) = address_from_path(device_path) else {
+            return;
+        };
+        let changed = {
+            let mut trackers = self.trackers.lock().unwrap();
+            trackers.get_mut(&address).and_then(|tracker| {
+                let previous = tracker.state;
+                tracker.state = Proximity::Gone;
+                tracker.connected = false;
+                (previous != Proximity::Gone).then(|| (tracker.info(&address), previous))
+            })
+        };
+        if let Some((info, previous)) = changed {
+            emit_proximity(app, info, previous);
+        }
+    }
+
+    fn expire<R: Runtime>(&self, app: &AppHandle<R>) {
+        let timeout = Duration::from_millis(self.config().gone_timeout_ms);
+        let mut trackers = self.trackers.lock().unwrap();
+        trackers.retain(|_, t| t.state != Proximity::Gone || t.connected || t.last_seen.elapsed() <= GONE_TTL);
+        let expired: Vec<(ProximityInfo, Proximity)> = trackers
+            .iter_mut()
+            .filter(|(_, t)| t.state != Proximity::Gone && !t.connected && t.last_seen.elapsed() > timeout)
+            .map(|(address, tracker)| {
+                let previous = tracker.state;
+                tracker.state = Proximity::Gone;
+                (tracker.info(address), previous)
+            })
+            .collect();
+        drop(trackers);
+        for (info, previous) in expired {
+            emit_proximity(app, info, previous);
+        }
+    }
+
+    /// Marks devices gone once they time out and forgets those gone for `GONE_TTL`.
+    pub fn spawn_ticker<R: Runtime>(app: AppHandle<R>) {
+        tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::time::sleep(TICK_INTERVAL).await;
+                app.state::<BluetoothManager>().presence.expire(&app);
+            }
+        });
+    }
+
+    pub fn get(&self, address: &str) -> Result<Option<ProximityInfo>> {
+        let address = normalize_address(address)?;
+        Ok(self.trackers.lock().unwrap().get(&address).map(|t| t.info(&address)))
+    }
+
+    pub fn list(&self) -> Vec<ProximityInfo> {
+        let mut list: Vec<ProximityInfo> = self
+            .trackers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(address, t)| t.info(address))
+            .collect();
+        list.sort_by(|a, b| a.address.cmp(&b.address));
+        list
+    }
+}
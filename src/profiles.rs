@@ -0,0 +1,92 @@
+use crate::{Error, Result};
+
+/// Base UUID suffix shared by every 16-bit Bluetooth SIG assigned number.
+const BASE_UUID_SUFFIX: &str = "-0000-1000-8000-00805f9b34fb";
+
+/// Friendly profile names and the 16-bit UUIDs that can implement them.
+///
+/// Some names map to more than one UUID (e.g. HID over classic vs. HID over GATT);
+/// the first one reported by the device wins.
+const PROFILE_ALIASES: &[(&str, &[u16])] = &[
+    ("spp", &[0x1101]),
+    ("dun", &[0x1103]),
+    ("opp", &[0x1105]),
+    ("ftp", &[0x1106]),
+    ("hsp-hs", &[0x1108]),
+    ("a2dp-source", &[0x110a]),
+    ("a2dp-sink", &[0x110b]),
+    ("avrcp-target", &[0x110c]),
+    ("avrcp", &[0x110e]),
+    ("avrcp-controller", &[0x110f]),
+    ("hsp-ag", &[0x1112]),
+    ("panu", &[0x1115]),
+    ("pan-nap", &[0x1116]),
+    ("pan-gn", &[0x1117]),
+    ("hfp-hf", &[0x111e]),
+    ("hfp-ag", &[0x111f]),
+    ("hid", &[0x1124, 0x1812]),
+    ("pbap-pse", &[0x112f]),
+    ("map-mse", &[0x1132]),
+    ("map-mns", &[0x1133]),
+    ("battery", &[0x180f]),
+];
+
+/// Expands a 16-bit assigned number into its full 128-bit UUID string.
+pub fn uuid_from_u16(short: u16) -> String {
+    format!("0000{:04x}{}", short, BASE_UUID_SUFFIX)
+}
+
+/// Returns true when `value` looks like a 128-bit UUID (8-4-4-4-12 hex groups).
+pub fn is_full_uuid(value: &str) -> bool {
+    let groups: Vec<&str> = value.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(g, len)| g.len() == len && g.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Resolves a raw UUID or a friendly profile name (e.g. "a2dp-sink", "hid")
+/// to the UUID that should be passed to `ConnectProfile`/`DisconnectProfile`.
+///
+/// Friendly names are matched against `device_uuids` so only profiles the device
+/// actually advertises are accepted.
+pub fn resolve_profile_uuid(profile: &str, device_uuids: &[String]) -> Result<String> {
+    let profile = profile.trim().to_lowercase();
+
+    if is_full_uuid(&profile) {
+        return Ok(profile);
+    }
+
+    // Short form such as "110b" or "0x110b"
+    let short_hex = profile.trim_start_matches("0x");
+    if short_hex.len() == 4 {
+        if let Ok(short) = u16::from_str_radix(short_hex, 16) {
+            return Ok(uuid_from_u16(short));
+        }
+    }
+
+    let candidates = PROFILE_ALIASES
+        .iter()
+        .find(|(name, _)| *name == profile)
+        .map(|(_, uuids)| *uuids)
+        .ok_or_else(|| Error::NotFound(format!("Unknown profile: {}", profile)))?;
+
+    candidates
+        .iter()
+        .map(|short| uuid_from_u16(*short))
+        .find(|uuid| device_uuids.iter().any(|u| u.eq_ignore_ascii_case(uuid)))
+        .ok_or_else(|| {
+            Error::NotFound(format!("Device does not support profile: {}", profile))
+        })
+}
+
+/// Like [`resolve_profile_uuid`] but without a device to match against: friendly
+/// names resolve to their first UUID. Used when registering local profiles.
+pub fn profile_uuid(profile: &str) -> Result<String> {
+    let name = profile.trim().to_lowercase();
+    match PROFILE_ALIASES.iter().find(|(alias, _)| *alias == name) {
+        Some((_, uuids)) => Ok(uuid_from_u16(uuids[0])),
+        None => resolve_profile_uuid(&name, &[]),
+    }
+}
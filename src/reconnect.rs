@@ -0,0 +1,317 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime};
+use tracing::{error, info, warn};
+
+use crate::connect;
+use crate::desktop::{fetch_adapters, fetch_device_info, BluetoothManager};
+use crate::device_lookup::{normalize_address, resolve_device_path};
+use crate::events::{emit_change, BluetoothEvent};
+use crate::models::{AutoReconnectInfo, ConnectOptions};
+use crate::{Error, Result};
+
+const STORE_FILE: &str = "bluetooth-auto-reconnect.json";
+
+const MAX_ATTEMPTS: u32 = 6;
+const ATTEMPT_TIMEOUT_MS: u64 = 10_000;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// After giving up, RSSI updates (device back in range) only retry once this has passed.
+const APPEARANCE_COOLDOWN: Duration = Duration::from_secs(30);
+
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+struct StoredDevices {
+    addresses: Vec<String>,
+}
+
+/// Auto-reconnect for favorite devices, keyed by address so it follows the device
+/// across adapters.
+///
+/// A reconnect loop runs per device with exponential backoff and gives up after a few
+/// attempts; the device coming back in range (RSSI) or its adapter powering on starts a
+/// new one. A user `disconnect_device` suspends it until the device connects again.
+#[derive(Default)]
+pub struct AutoReconnect {
+    devices: Mutex<HashSet<String>>,
+    user_disconnected: Mutex<HashSet<String>>,
+    /// Running loops by address; a loop stops once its generation is no longer current.
+    tasks: Mutex<HashMap<String, u64>>,
+    next_generation: Mutex<u64>,
+    gave_up: Mutex<HashMap<String, Instant>>,
+    store: Mutex<Option<PathBuf>>,
+}
+
+fn emit_status<R: Runtime>(app: &AppHandle<R>, address: &str, state: &str, extra: serde_json::Value) {
+    let mut data = serde_json::json!({ "address": address, "state": state });
+    if let (Some(data), serde_json::Value::Object(extra)) = (data.as_object_mut(), extra) {
+        data.extend(extra);
+    }
+    emit_change(app, BluetoothEvent::other("auto-reconnect-status", data));
+}
+
+fn attempt_id(address: &str) -> String {
+    format!("auto-reconnect:{}", address)
+}
+
+impl AutoReconnect {
+    /// Loads the auto-reconnect devices from the app config dir.
+    pub fn load<R: Runtime>(&self, app: &AppHandle<R>) {
+        let Ok(dir) = app.path().app_config_dir() else {
+            return;
+        };
+        let path = dir.join(STORE_FILE);
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            match serde_json::from_str::<StoredDevices>(&content) {
+                Ok(stored) => self.devices.lock().unwrap().extend(stored.addresses),
+                Err(e) => error!("Invalid {}: {}", path.display(), e),
+            }
+        }
+        *self.store.lock().unwrap() = Some(path);
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = self.store.lock().unwrap().clone() else {
+            return Ok(());
+        };
+        let mut addresses: Vec<String> = self.devices.lock().unwrap().iter().cloned().collect();
+        addresses.sort();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| Error::CommandError(format!("Error creating {}: {}", dir.display(), e)))?;
+        }
+        let content = serde_json::to_string_pretty(&StoredDevices { addresses }).unwrap_or_default();
+        std::fs::write(&path, content)
+            .map_err(|e| Error::CommandError(format!("Error writing {}: {}", path.display(), e)))
+    }
+
+    fn is_enabled(&self, address: &str) -> bool {
+        self.devices.lock().unwrap().contains(address)
+    }
+
+    fn is_current(&self, address: &str, generation: u64) -> bool {
+        self.tasks.lock().unwrap().get(address) == Some(&generation)
+    }
+
+    pub fn list(&self) -> Vec<AutoReconnectInfo> {
+        let tasks = self.tasks.lock().unwrap();
+        let user_disconnected = self.user_disconnected.lock().unwrap();
+        let mut list: Vec<AutoReconnectInfo> = self
+            .devices
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|address| AutoReconnectInfo {
+                address: address.clone(),
+                reconnecting: tasks.contains_key(address),
+                user_disconnected: user_disconnected.contains(address),
+            })
+            .collect();
+        list.sort_by(|a, b| a.address.cmp(&b.address));
+        list
+    }
+
+    pub fn set<R: Runtime>(&self, app: &AppHandle<R>, address: &str, enabled: bool) -> Result<()> {
+        let address = normalize_address(address)?;
+        if enabled {
+            self.devices.lock().unwrap().insert(address.clone());
+            self.user_disconnected.lock().unwrap().remove(&address);
+        } else {
+            self.devices.lock().unwrap().remove(&address);
+            self.stop(app, &address);
+        }
+        self.save()?;
+        if enabled {
+            self.trigger(app, &address, "enabled");
+        }
+        Ok(())
+    }
+
+    fn stop<R: Runtime>(&self, app: &AppHandle<R>, address: &str) {
+        if self.tasks.lock().unwrap().remove(address).is_some() {
+            let _ = app
+                .state::<BluetoothManager>()
+                .connect_attempts
+                .cancel(&attempt_id(address));
+        }
+    }
+
+    /// Called before a user-requested disconnect so the loop doesn't fight it.
+    pub fn on_user_disconnect<R: Runtime>(&self, app: &AppHandle<R>, address: &str) {
+        if !self.is_enabled(address) {
+            return;
+        }
+        self.user_disconnected.lock().unwrap().insert(address.to_string());
+        self.stop(app, address);
+        emit_status(app, address, "suspended", serde_json::json!({ "reason": "user-disconnect" }));
+    }
+
+    /// Any connection, ours or not, ends the user-disconnect suspension.
+    pub fn on_connected(&self, address: &str) {
+        self.user_disconnected.lock().unwrap().remove(address);
+        self.gave_up.lock().unwrap().remove(address);
+    }
+
+    pub fn on_disconnected<R: Runtime>(&self, app: &AppHandle<R>, address: &str) {
+        self.trigger(app, address, "disconnected");
+    }
+
+    /// RSSI showed up for a device: it is back in range. Called for every RSSI sample, so
+    /// it only looks at in-memory state before triggering.
+    pub fn on_seen<R: Runtime>(&self, app: &AppHandle<R>, device_path: &str, address: &str) {
+        if !self.is_enabled(address) {
+            return;
+        }
+        let connected = app
+            .state::<BluetoothManager>()
+            .objects
+            .device(device_path)
+            .map(|d| d.connected)
+            .unwrap_or(false);
+        if connected {
+            return;
+        }
+        let cooling_down = self
+            .gave_up
+            .lock()
+            .unwrap()
+            .get(address)
+            .map(|at| at.elapsed() < APPEARANCE_COOLDOWN)
+            .unwrap_or(false);
+        if !cooling_down {
+            self.trigger(app, address, "in-range");
+        }
+    }
+
+    /// An adapter powered on: retry every device that isn't connected.
+    pub fn on_adapter_powered<R: Runtime>(&self, app: &AppHandle<R>) {
+        self.gave_up.lock().unwrap().clear();
+        self.trigger_all(app, "adapter-powered");
+    }
+
+    pub fn trigger_all<R: Runtime>(&self, app: &AppHandle<R>, reason: &str) {
+        let devices: Vec<String> = self.devices.lock().unwrap().iter().cloned().collect();
+        for address in devices {
+            self.trigger(app, &address, reason);
+        }
+    }
+
+    /// Starts a reconnect loop for `address` unless one is running or it doesn't apply.
+    pub fn trigger<R: Runtime>(&self, app: &AppHandle<R>, address: &str, reason: &str) {
+        if !self.is_enabled(address) || self.user_disconnected.lock().unwrap().contains(address) {
+            return;
+        }
+        let generation = {
+            let mut tasks = self.tasks.lock().unwrap();
+            if tasks.contains_key(address) {
+                return;
+            }
+            let mut next = self.next_generation.lock().unwrap();
+            *next += 1;
+            tasks.insert(address.to_string(), *next);
+            *next
+        };
+
+        let app = app.clone();
+        let address = address.to_string();
+        let reason = reason.to_string();
+        tauri::async_runtime::spawn(async move {
+            let state = app.state::<BluetoothManager>();
+            state
+                .auto_reconnect
+                .run(&app, &state, &address, generation, &reason)
+                .await;
+        });
+    }
+
+    async fn run<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        state: &BluetoothManager,
+        address: &str,
+        generation: u64,
+        reason: &str,
+    ) {
+        info!("Auto-reconnect for {} ({})", address, reason);
+        let mut backoff = INITIAL_BACKOFF;
+        let mut outcome = "gave-up";
+        // Loops that end before trying (already connected, nothing to reach) stay silent
+        let mut attempted = false;
+
+        for attempt in 1..=MAX_ATTEMPTS {
+            if !self.is_current(address, generation) {
+                return;
+            }
+
+            let device = match resolve_device_path(&state.conn, address, None).await {
+                Ok(path) => fetch_device_info(&state.conn, path).await.ok(),
+                Err(_) => None,
+            };
+            let Some(device) = device else {
+                // Unknown to BlueZ until it is seen again
+                outcome = "waiting-device";
+                break;
+            };
+            if device.connected {
+                outcome = "connected";
+                break;
+            }
+            let powered = fetch_adapters(&state.conn)
+                .await
+                .map(|adapters| adapters.iter().any(|a| a.path == device.adapter && a.powered))
+                .unwrap_or(false);
+            if !powered {
+                outcome = "waiting-adapter";
+                break;
+            }
+
+            emit_status(app, address, "connecting", serde_json::json!({
+                "device": device.path,
+                "attempt": attempt,
+                "attempts": MAX_ATTEMPTS,
+                "reason": reason,
+            }));
+            attempted = true;
+            let options = ConnectOptions {
+                id: Some(attempt_id(address)),
+                timeout_ms: Some(ATTEMPT_TIMEOUT_MS),
+                ..Default::default()
+            };
+            match connect::connect_with_options(app, &state.conn, &state.connect_attempts, &device.path, options).await {
+                Ok(_) => {
+                    outcome = "connected";
+                    break;
+                }
+                Err(Error::Canceled(_)) => return,
+                Err(e) => {
+                    warn!("Auto-reconnect attempt {}/{} for {} failed: {}", attempt, MAX_ATTEMPTS, address, e);
+                    if attempt < MAX_ATTEMPTS {
+                        emit_status(app, address, "retrying", serde_json::json!({
+                            "device": device.path,
+                            "attempt": attempt,
+                            "error": e.to_string(),
+                            "retryInMs": backoff.as_millis() as u64,
+                        }));
+                        tokio::time::sleep(backoff).await;
+                        backoff = (backoff * 2).min(MAX_BACKOFF);
+                    }
+                }
+            }
+        }
+
+        {
+            let mut tasks = self.tasks.lock().unwrap();
+            if tasks.get(address) != Some(&generation) {
+                return;
+            }
+            tasks.remove(address);
+        }
+        if outcome == "gave-up" {
+            self.gave_up.lock().unwrap().insert(address.to_string(), Instant::now());
+        }
+        if attempted {
+            emit_status(app, address, outcome, serde_json::json!({}));
+        }
+    }
+}
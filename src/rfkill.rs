@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::Duration;
+use tauri::{AppHandle, Runtime};
+use tracing::{error, info, warn};
+
+use crate::events::{emit_change, BluetoothEvent};
+use crate::models::{RfkillState};
+use crate::{Error, Result};
+
+const RFKILL_DEV: &str = "/dev/rfkill";
+const RFKILL_SYSFS: &str = "/sys/class/rfkill";
+
+// <linux/rfkill.h>
+const RFKILL_TYPE_BLUETOOTH: u8 = 2;
+const RFKILL_OP_DEL: u8 = 1;
+const RFKILL_OP_CHANGE: u8 = 2;
+const RFKILL_OP_CHANGE_ALL: u8 = 3;
+/// `struct rfkill_event` v1: idx (u32), type, op, soft, hard.
+const RFKILL_EVENT_SIZE: usize = 8;
+
+/// How long `unblock_for_power_on` waits for the radio to come back after a soft unblock.
+const UNBLOCK_SETTLE: Duration = Duration::from_secs(2);
+
+struct RfkillEvent {
+    index: u32,
+    kind: u8,
+    op: u8,
+    soft: bool,
+    hard: bool,
+}
+
+impl RfkillEvent {
+    fn parse(buf: &[u8; RFKILL_EVENT_SIZE]) -> Self {
+        Self {
+            index: u32::from_ne_bytes([buf[0], buf[1], buf[2], buf[3]]),
+            kind: buf[4],
+            op: buf[5],
+            soft: buf[6] != 0,
+            hard: buf[7] != 0,
+        }
+    }
+
+    fn encode(index: u32, op: u8, soft: bool) -> [u8; RFKILL_EVENT_SIZE] {
+        let mut buf = [0u8; RFKILL_EVENT_SIZE];
+        buf[..4].copy_from_slice(&index.to_ne_bytes());
+        buf[4] = RFKILL_TYPE_BLUETOOTH;
+        buf[5] = op;
+        buf[6] = soft as u8;
+        buf
+    }
+}
+
+fn read_sysfs(dir: &Path, file: &str) -> Option<String> {
+    fs::read_to_string(dir.join(file)).ok().map(|s| s.trim().to_string())
+}
+
+/// Rfkill switches named `hciN` belong to that adapter; any other Bluetooth switch
+/// (laptop hotkeys, platform drivers) cuts every adapter.
+fn adapter_for(name: &str) -> Option<String> {
+    name.starts_with("hci").then(|| format!("/org/bluez/{}", name))
+}
+
+fn state_from_sysfs(index: u32) -> Option<RfkillState> {
+    let dir = Path::new(RFKILL_SYSFS).join(format!("rfkill{}", index));
+    if read_sysfs(&dir, "type")? != "bluetooth" {
+        return None;
+    }
+    let name = read_sysfs(&dir, "name").unwrap_or_default();
+    Some(RfkillState {
+        index,
+        adapter: adapter_for(&name),
+        name,
+        soft_blocked: read_sysfs(&dir, "soft").as_deref() == Some("1"),
+        hard_blocked: read_sysfs(&dir, "hard").as_deref() == Some("1"),
+    })
+}
+
+/// Bluetooth rfkill switches as currently reported by sysfs (no privileges needed).
+pub fn list() -> Vec<RfkillState> {
+    let Ok(entries) = fs::read_dir(RFKILL_SYSFS) else {
+        return Vec::new();
+    };
+    let mut states: Vec<RfkillState> = entries
+        .flatten()
+        .filter_map(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_prefix("rfkill"))
+                .and_then(|index| index.parse::<u32>().ok())
+        })
+        .filter_map(state_from_sysfs)
+        .collect();
+    states.sort_by_key(|s| s.index);
+    states
+}
+
+/// Switches that affect `adapter_path`: its own plus the platform-wide ones.
+fn switches_for(adapter_path: &str) -> Vec<RfkillState> {
+    list()
+        .into_iter()
+        .filter(|s| s.adapter.as_deref().map(|a| a == adapter_path).unwrap_or(true))
+        .collect()
+}
+
+fn write_event(event: [u8; RFKILL_EVENT_SIZE]) -> Result<()> {
+    let mut dev = OpenOptions::new()
+        .write(true)
+        .open(RFKILL_DEV)
+        .map_err(|e| Error::CommandError(format!("Cannot open {}: {}", RFKILL_DEV, e)))?;
+    dev.write_all(&event)
+        .map_err(|e| Error::CommandError(format!("Error writing to {}: {}", RFKILL_DEV, e)))
+}
+
+/// Soft-blocks or unblocks one adapter, or every Bluetooth radio when `adapter_path` is `None`.
+pub fn set_blocked(adapter_path: Option<&str>, blocked: bool) -> Result<()> {
+    let Some(adapter_path) = adapter_path else {
+        return write_event(RfkillEvent::encode(0, RFKILL_OP_CHANGE_ALL, blocked));
+    };
+
+    let switches = switches_for(adapter_path);
+    if switches.is_empty() {
+        return Err(Error::NotFound(format!("No rfkill switch for {}", adapter_path)));
+    }
+    if !blocked {
+        if let Some(hard) = switches.iter().find(|s| s.hard_blocked) {
+            return Err(Error::RfkillBlocked(format!("{} is hard blocked", hard.name)));
+        }
+    }
+
+    // Blocking only needs the adapter's own switch; unblocking clears platform ones too
+    for switch in switches
+        .iter()
+        .filter(|s| s.soft_blocked != blocked && (!blocked || s.adapter.is_some()))
+    {
+        write_event(RfkillEvent::encode(switch.index, RFKILL_OP_CHANGE, blocked))?;
+    }
+    Ok(())
+}
+
+/// Clears a soft block before powering on, so `Powered = true` doesn't fail with
+/// "Blocked through rfkill". Hard blocks are reported as an error.
+pub async fn unblock_for_power_on(adapter_path: &str) -> Result<()> {
+    let switches = switches_for(adapter_path);
+    if let Some(hard) = switches.iter().find(|s| s.hard_blocked) {
+        return Err(Error::RfkillBlocked(format!(
+            "{} is hard blocked (hardware switch)",
+            hard.name
+        )));
+    }
+    if !switches.iter().any(|s| s.soft_blocked) {
+        return Ok(());
+    }
+
+    info!("Soft-unblocking {} before powering on", adapter_path);
+    set_blocked(Some(adapter_path), false)?;
+
+    let deadline = tokio::time::Instant::now() + UNBLOCK_SETTLE;
+    while switches_for(adapter_path).iter().any(|s| s.soft_blocked) {
+        if tokio::time::Instant::now() >= deadline {
+            return Err(Error::RfkillBlocked(format!("{} is still soft blocked", adapter_path)));
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    Ok(())
+}
+
+/// Follows `/dev/rfkill` on a dedicated thread and emits `rfkill-changed` for Bluetooth
+/// switches whose state actually changes.
+pub fn spawn_watcher<R: Runtime>(app: AppHandle<R>) {
+    let mut dev = match File::open(RFKILL_DEV) {
+        Ok(dev) => dev,
+        Err(e) => {
+            warn!("rfkill events unavailable ({}): {}", RFKILL_DEV, e);
+            return;
+        }
+    };
+
+    let spawned = std::thread::Builder::new()
+        .name("bluetooth-rfkill".to_string())
+        .spawn(move || {
+            let mut known: HashMap<u32, RfkillState> = HashMap::new();
+            let mut buf = [0u8; RFKILL_EVENT_SIZE];
+            loop {
+                if let Err(e) = dev.read_exact(&mut buf) {
+                    error!("Error reading {}: {}", RFKILL_DEV, e);
+                    break;
+                }
+                let event = RfkillEvent::parse(&buf);
+                if event.kind != RFKILL_TYPE_BLUETOOTH {
+                    continue;
+                }
+
+                let change = if event.op == RFKILL_OP_DEL {
+                    match known.remove(&event.index) {
+                        Some(state) => BluetoothEvent::RfkillRemoved { index: state.index, name: state.name },
+                        None => continue,
+                    }
+                } else {
+                    let name = read_sysfs(&Path::new(RFKILL_SYSFS).join(format!("rfkill{}", event.index)), "name")
+                        .unwrap_or_default();
+                    let state = RfkillState {
+                        index: event.index,
+                        adapter: adapter_for(&name),
+                        name,
+                        soft_blocked: event.soft,
+                        hard_blocked: event.hard,
+                    };
+                    if known.get(&event.index) == Some(&state) {
+                        continue;
+                    }
+                    let first_seen = known.insert(event.index, state.clone()).is_none();
+                    // The initial burst of ADD events only fills the cache
+                    if first_seen && event.op != RFKILL_OP_CHANGE {
+                        continue;
+                    }
+                    BluetoothEvent::RfkillChanged(state)
+                };
+
+                emit_change(&app, change);
+            }
+        });
+
+    if let Err(e) = spawned {
+        error!("Error starting rfkill watcher: {}", e);
+    }
+}
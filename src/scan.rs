@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{ipc::Channel, AppHandle, Manager, Runtime};
+
+use crate::desktop::BluetoothManager;
+use crate::models::{DeviceInfo, ScanEvent};
+
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+struct Tracked {
+    last_seen: Instant,
+    lost: bool,
+}
+
+/// One `start_scan` caller listening on a Channel.
+struct ScanSession {
+    adapter: String,
+    channel: Channel<ScanEvent>,
+    next_seq: u64,
+    stale_timeout: Duration,
+    devices: HashMap<String, Tracked>,
+}
+
+impl ScanSession {
+    fn covers(&self, device_path: &str) -> bool {
+        device_path.starts_with(&format!("{}/", self.adapter))
+    }
+
+    /// Returns false once the webview stopped taking messages.
+    fn send(&mut self, kind: &str, path: Option<&str>, device: Option<DeviceInfo>, devices: Vec<DeviceInfo>) -> bool {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.channel
+            .send(ScanEvent {
+                seq,
+                kind: kind.to_string(),
+                path: path.map(|p| p.to_string()),
+                device,
+                devices,
+            })
+            .is_ok()
+    }
+}
+
+/// Discovery results streamed per caller: the cached devices of the adapter first, then
+/// `found`/`updated`/`lost` in signal order.
+///
+/// BlueZ keeps devices around for minutes after they went quiet, so a device counts as
+/// lost once no property changed for the stale timeout (connected devices never do).
+#[derive(Default)]
+pub struct ScanSessions {
+    sessions: Mutex<HashMap<u64, ScanSession>>,
+    next_id: AtomicU64,
+}
+
+fn snapshot(manager: &BluetoothManager, device_path: &str) -> Option<DeviceInfo> {
+    let mut device = manager.objects.device(device_path)?;
+    manager.device_history.annotate(&mut device);
+    Some(device)
+}
+
+impl ScanSessions {
+    /// Starts a session and sends the `initial` message with the cached devices.
+    pub fn start(
+        &self,
+        manager: &BluetoothManager,
+        adapter_path: &str,
+        channel: Channel<ScanEvent>,
+        stale_timeout_ms: u64,
+    ) {
+        // Holding the sessions lock across the snapshot and the insert keeps a change that
+        // lands in between from being missed: `on_device_changed` waits for the session
+        let mut sessions = self.sessions.lock().unwrap();
+        let mut devices = manager.objects.devices(adapter_path);
+        manager.device_history.annotate_all(&mut devices);
+        let now = Instant::now();
+        let mut session = ScanSession {
+            adapter: adapter_path.to_string(),
+            channel,
+            next_seq: 0,
+            stale_timeout: Duration::from_millis(stale_timeout_ms),
+            devices: devices
+                .iter()
+                .map(|d| (d.path.clone(), Tracked { last_seen: now, lost: false }))
+                .collect(),
+        };
+        if session.send("initial", None, None, devices) {
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+            sessions.insert(id, session);
+        }
+    }
+
+    /// Ends the sessions of an adapter with a `stopped` message.
+    pub fn stop(&self, adapter_path: &str) {
+        self.sessions.lock().unwrap().retain(|_, session| {
+            if session.adapter != adapter_path {
+                return true;
+            }
+            session.send("stopped", None, None, Vec::new());
+            false
+        });
+    }
+
+    /// A device showed up or one of its properties changed.
+    pub fn on_device_changed(&self, manager: &BluetoothManager, device_path: &str) {
+        let mut sessions = self.sessions.lock().unwrap();
+        if !sessions.values().any(|s| s.covers(device_path)) {
+            return;
+        }
+        let device = snapshot(manager, device_path);
+        sessions.retain(|_, session| {
+            if !session.covers(device_path) {
+                return true;
+            }
+            let tracked = session.devices.entry(device_path.to_string()).or_insert(Tracked {
+                last_seen: Instant::now(),
+                lost: true,
+            });
+            let kind = if tracked.lost { "found" } else { "updated" };
+            tracked.last_seen = Instant::now();
+            tracked.lost = false;
+            session.send(kind, Some(device_path), device.clone(), Vec::new())
+        });
+    }
+
+    /// BlueZ dropped the device object.
+    pub fn on_device_removed(&self, device_path: &str) {
+        self.sessions.lock().unwrap().retain(|_, session| {
+            match session.devices.remove(device_path) {
+                Some(tracked) if !tracked.lost => session.send("lost", Some(device_path), None, Vec::new()),
+                _ => true,
+            }
+        });
+    }
+
+    fn expire(&self, manager: &BluetoothManager) {
+        self.sessions.lock().unwrap().retain(|_, session| {
+            let stale: Vec<String> = session
+                .devices
+                .iter()
+                .filter(|(_, t)| !t.lost && t.last_seen.elapsed() > session.stale_timeout)
+                .map(|(path, _)| path.clone())
+                .collect();
+            for path in stale {
+                let device = snapshot(manager, &path);
+                let connected = device.as_ref().map(|d| d.connected).unwrap_or(false);
+                if let Some(tracked) = session.devices.get_mut(&path) {
+                    if connected {
+                        tracked.last_seen = Instant::now();
+                        continue;
+                    }
+                    tracked.lost = true;
+                }
+                if !session.send("lost", Some(&path), device, Vec::new()) {
+                    return false;
+                }
+            }
+            true
+        });
+    }
+
+    /// Marks quiet devices lost.
+    pub fn spawn_ticker<R: Runtime>(app: AppHandle<R>) {
+        tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::time::sleep(TICK_INTERVAL).await;
+                let manager = app.state::<BluetoothManager>();
+                manager.scans.expire(&manager);
+            }
+        });
+    }
+}
@@ -0,0 +1,568 @@
+use futures::Stream;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tauri::{AppHandle, Runtime};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, ReadHalf, WriteHalf};
+use tokio::sync::{mpsc, oneshot, watch};
+use tracing::{error, info, warn};
+use zbus::{Connection, Proxy};
+
+use crate::custom_profiles::{CustomProfiles, ProfileStream};
+use crate::device_lookup::resolve_device_path;
+use crate::events::{emit_change, BluetoothEvent};
+use crate::models::{ProfileOptions, SppConnectionInfo, SppOptions};
+use crate::profiles::uuid_from_u16;
+use crate::{Error, Result};
+
+const SPP_UUID: u16 = 0x1101;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+const DEFAULT_RECONNECT_ATTEMPTS: u32 = 5;
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Pending writes per connection; `write` waits once the queue is full.
+const WRITE_QUEUE: usize = 32;
+/// Undelivered messages for Rust consumers; reading stops once the queue is full.
+const READ_QUEUE: usize = 64;
+/// Line mode gives up waiting for a delimiter past this size and delivers what it has.
+const MAX_MESSAGE: usize = 64 * 1024;
+
+/// How the byte stream is cut into messages.
+#[derive(Clone, Debug)]
+enum Framing {
+    Raw,
+    Line(Vec<u8>),
+    /// Two-byte big-endian length prefix per message.
+    Framed,
+}
+
+impl Framing {
+    fn from_options(options: &SppOptions) -> Result<Self> {
+        match options.mode.as_deref().unwrap_or("raw") {
+            "raw" => Ok(Framing::Raw),
+            "line" => {
+                let delimiter = options.delimiter.clone().unwrap_or_else(|| "\n".to_string());
+                if delimiter.is_empty() {
+                    return Err(Error::CommandError("Line delimiter cannot be empty".to_string()));
+                }
+                Ok(Framing::Line(delimiter.into_bytes()))
+            }
+            "framed" => Ok(Framing::Framed),
+            other => Err(Error::CommandError(format!(
+                "Unknown SPP mode: {} (expected \"raw\", \"line\" or \"framed\")",
+                other
+            ))),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Framing::Raw => "raw",
+            Framing::Line(_) => "line",
+            Framing::Framed => "framed",
+        }
+    }
+
+    /// Turns one outgoing message into the bytes written to the socket.
+    fn encode(&self, data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Framing::Raw => Ok(data.to_vec()),
+            Framing::Line(delimiter) => {
+                let mut out = data.to_vec();
+                if !out.ends_with(delimiter) {
+                    out.extend_from_slice(delimiter);
+                }
+                Ok(out)
+            }
+            Framing::Framed => {
+                let len = u16::try_from(data.len()).map_err(|_| {
+                    Error::CommandError(format!("Frame too large: {} bytes (max 65535)", data.len()))
+                })?;
+                let mut out = Vec::with_capacity(data.len() + 2);
+                out.extend_from_slice(&len.to_be_bytes());
+                out.extend_from_slice(data);
+                Ok(out)
+            }
+        }
+    }
+}
+
+/// Accumulates incoming bytes and yields complete messages.
+struct Decoder {
+    framing: Framing,
+    buf: Vec<u8>,
+}
+
+impl Decoder {
+    fn new(framing: Framing) -> Self {
+        Self { framing, buf: Vec::new() }
+    }
+
+    fn push(&mut self, data: &[u8]) -> Vec<Vec<u8>> {
+        let mut messages = Vec::new();
+        match &self.framing {
+            Framing::Raw => messages.push(data.to_vec()),
+            Framing::Line(delimiter) => {
+                self.buf.extend_from_slice(data);
+                while let Some(pos) = self.buf.windows(delimiter.len()).position(|w| w == delimiter.as_slice()) {
+                    let mut line: Vec<u8> = self.buf.drain(..pos + delimiter.len()).collect();
+                    line.truncate(pos);
+                    // Devices that end lines with CRLF while we split on LF
+                    if delimiter.as_slice() == b"\n" && line.last() == Some(&b'\r') {
+                        line.pop();
+                    }
+                    messages.push(line);
+                }
+                if self.buf.len() > MAX_MESSAGE {
+                    messages.push(std::mem::take(&mut self.buf));
+                }
+            }
+            Framing::Framed => {
+                self.buf.extend_from_slice(data);
+                while self.buf.len() >= 2 {
+                    let len = u16::from_be_bytes([self.buf[0], self.buf[1]]) as usize;
+                    if self.buf.len() < len + 2 {
+                        break;
+                    }
+                    messages.push(self.buf[2..len + 2].to_vec());
+                    self.buf.drain(..len + 2);
+                }
+            }
+        }
+        messages
+    }
+}
+
+/// Where decoded messages go: `spp-data` events, or a channel for Rust consumers.
+enum Output<R: Runtime> {
+    Events(AppHandle<R>),
+    Channel(mpsc::Sender<Vec<u8>>),
+}
+
+/// An SPP connection for Rust consumers. Messages are already split according to the
+/// mode; reconnection happens behind it. Dropping it closes the connection.
+pub struct SppStream {
+    pub id: String,
+    incoming: mpsc::Receiver<Vec<u8>>,
+    outgoing: mpsc::Sender<Vec<u8>>,
+    framing: Framing,
+}
+
+impl SppStream {
+    /// Next message, or `None` once the connection is gone for good.
+    pub async fn recv(&mut self) -> Option<Vec<u8>> {
+        self.incoming.recv().await
+    }
+
+    /// Queues one message; waits while the write queue is full.
+    pub async fn send(&self, data: &[u8]) -> Result<()> {
+        let bytes = self.framing.encode(data)?;
+        self.outgoing
+            .send(bytes)
+            .await
+            .map_err(|_| Error::NotFound(format!("SPP connection closed: {}", self.id)))
+    }
+}
+
+impl Stream for SppStream {
+    type Item = Vec<u8>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.incoming.poll_recv(cx)
+    }
+}
+
+struct SppSession {
+    address: String,
+    device: String,
+    framing: Framing,
+    outgoing: mpsc::Sender<Vec<u8>>,
+    connected: Arc<AtomicBool>,
+    paused: watch::Sender<bool>,
+    closed: watch::Sender<bool>,
+}
+
+type Waiters = Arc<Mutex<HashMap<String, oneshot::Sender<ProfileStream>>>>;
+type Sessions = Arc<Mutex<HashMap<String, SppSession>>>;
+
+/// SPP client connections on top of a plugin-registered SPP profile.
+#[derive(Default)]
+pub struct SppClients {
+    registered: tokio::sync::Mutex<bool>,
+    waiters: Waiters,
+    sessions: Sessions,
+    next_id: AtomicU64,
+}
+
+fn emit<R: Runtime>(app: &AppHandle<R>, change_type: &str, data: serde_json::Value) {
+    emit_change(app, BluetoothEvent::other(change_type, data));
+}
+
+/// Asks BlueZ to connect the SPP profile and waits for the socket in `NewConnection`.
+async fn open_stream(conn: &Connection, waiters: &Waiters, device_path: &str) -> Result<ProfileStream> {
+    let (tx, rx) = oneshot::channel();
+    {
+        // Sockets are matched to waiters by device only, so one connect at a time per device
+        let mut waiters = waiters.lock().unwrap();
+        if waiters.contains_key(device_path) {
+            return Err(Error::CommandError(format!(
+                "An SPP connection to {} is already being opened",
+                device_path
+            )));
+        }
+        waiters.insert(device_path.to_string(), tx);
+    }
+
+    let proxy = Proxy::new(conn, "org.bluez", device_path, "org.bluez.Device1").await?;
+    if let Err(e) = proxy
+        .call_method("ConnectProfile", &(uuid_from_u16(SPP_UUID),))
+        .await
+    {
+        let msg = e.to_string();
+        // The socket may already be on its way
+        if !msg.contains("InProgress") && !msg.contains("AlreadyConnected") {
+            waiters.lock().unwrap().remove(device_path);
+            return Err(e.into());
+        }
+    }
+
+    match tokio::time::timeout(CONNECT_TIMEOUT, rx).await {
+        Ok(Ok(stream)) => Ok(stream),
+        _ => {
+            waiters.lock().unwrap().remove(device_path);
+            Err(Error::CommandError(format!("Timed out opening SPP socket to {}", device_path)))
+        }
+    }
+}
+
+async fn read_loop<R: Runtime>(
+    id: &str,
+    mut reader: ReadHalf<ProfileStream>,
+    decoder: &mut Decoder,
+    output: &Output<R>,
+    paused: &mut watch::Receiver<bool>,
+) -> String {
+    let mut buf = vec![0u8; 4096];
+    loop {
+        // While paused nothing is read, so RFCOMM flow control throttles the device
+        while *paused.borrow_and_update() {
+            if paused.changed().await.is_err() {
+                return "closed".to_string();
+            }
+        }
+
+        let n = match reader.read(&mut buf).await {
+            Ok(0) => return "closed by remote".to_string(),
+            Ok(n) => n,
+            Err(e) => return e.to_string(),
+        };
+
+        for message in decoder.push(&buf[..n]) {
+            match output {
+                Output::Events(app) => {
+                    let text = matches!(decoder.framing, Framing::Line(_))
+                        .then(|| String::from_utf8_lossy(&message).to_string());
+                    emit(app, "spp-data", serde_json::json!({ "id": id, "data": message, "text": text }));
+                }
+                Output::Channel(tx) => {
+                    if tx.send(message).await.is_err() {
+                        return "dropped".to_string();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Resolves once a Rust consumer has dropped its [`SppStream`].
+async fn output_closed<R: Runtime>(output: &Output<R>) {
+    match output {
+        Output::Channel(tx) => tx.closed().await,
+        Output::Events(_) => futures::future::pending().await,
+    }
+}
+
+async fn write_loop(mut writer: WriteHalf<ProfileStream>, outgoing: &mut mpsc::Receiver<Vec<u8>>) -> String {
+    while let Some(bytes) = outgoing.recv().await {
+        if let Err(e) = writer.write_all(&bytes).await {
+            return e.to_string();
+        }
+    }
+    "closed".to_string()
+}
+
+struct SessionTask<R: Runtime> {
+    id: String,
+    device: String,
+    conn: Connection,
+    waiters: Waiters,
+    sessions: Sessions,
+    framing: Framing,
+    output: Output<R>,
+    events: AppHandle<R>,
+    reconnect: bool,
+    max_attempts: u32,
+    connected: Arc<AtomicBool>,
+}
+
+impl<R: Runtime> SessionTask<R> {
+    async fn run(
+        self,
+        mut stream: ProfileStream,
+        mut outgoing: mpsc::Receiver<Vec<u8>>,
+        mut paused: watch::Receiver<bool>,
+        mut closed: watch::Receiver<bool>,
+    ) {
+        loop {
+            let socket_closer = stream.share();
+            let (reader, writer) = tokio::io::split(stream);
+            let mut decoder = Decoder::new(self.framing.clone());
+
+            let reason = tokio::select! {
+                reason = read_loop(&self.id, reader, &mut decoder, &self.output, &mut paused) => reason,
+                reason = write_loop(writer, &mut outgoing) => reason,
+                _ = closed.changed() => "closed".to_string(),
+                _ = output_closed(&self.output) => "dropped".to_string(),
+            };
+            let _ = socket_closer.close();
+            self.connected.store(false, Ordering::Relaxed);
+
+            let user_closed = *closed.borrow() || reason == "closed" || reason == "dropped";
+            let reconnecting = self.reconnect && !user_closed;
+            emit(&self.events, "spp-disconnected", serde_json::json!({
+                "id": self.id,
+                "reason": reason,
+                "reconnecting": reconnecting,
+            }));
+            if !reconnecting {
+                break;
+            }
+
+            match self.reconnect(&mut closed).await {
+                Some(new_stream) => {
+                    stream = new_stream;
+                    self.connected.store(true, Ordering::Relaxed);
+                    emit(&self.events, "spp-connected", serde_json::json!({ "id": self.id, "device": self.device }));
+                }
+                None => {
+                    emit(&self.events, "spp-disconnected", serde_json::json!({
+                        "id": self.id,
+                        "reason": "reconnect failed",
+                        "reconnecting": false,
+                    }));
+                    break;
+                }
+            }
+        }
+
+        self.sessions.lock().unwrap().remove(&self.id);
+        info!("SPP connection {} finished", self.id);
+    }
+
+    async fn reconnect(&self, closed: &mut watch::Receiver<bool>) -> Option<ProfileStream> {
+        let mut backoff = Duration::from_secs(1);
+        for attempt in 1..=self.max_attempts {
+            emit(&self.events, "spp-reconnecting", serde_json::json!({ "id": self.id, "attempt": attempt }));
+            tokio::select! {
+                _ = tokio::time::sleep(backoff) => {}
+                _ = closed.changed() => return None,
+            }
+
+            match open_stream(&self.conn, &self.waiters, &self.device).await {
+                Ok(stream) => return Some(stream),
+                Err(e) => warn!("SPP reconnect {} attempt {} failed: {}", self.id, attempt, e),
+            }
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+        None
+    }
+}
+
+impl SppClients {
+    /// Registers the SPP client profile once and routes incoming sockets to whoever is waiting.
+    async fn ensure_registered<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        conn: &Connection,
+        profiles: &CustomProfiles,
+    ) -> Result<()> {
+        let mut registered = self.registered.lock().await;
+        if *registered {
+            return Ok(());
+        }
+
+        let options = ProfileOptions {
+            uuid: uuid_from_u16(SPP_UUID),
+            name: Some("Serial Port".to_string()),
+            role: Some("client".to_string()),
+            // Otherwise Device1.Connect may open SPP too, and nobody waits for that socket
+            auto_connect: Some(false),
+            ..Default::default()
+        };
+        let mut streams = profiles.register_streams(app.clone(), conn, options).await?;
+
+        let waiters = self.waiters.clone();
+        tauri::async_runtime::spawn(async move {
+            while let Some(stream) = streams.recv().await {
+                let waiter = waiters.lock().unwrap().remove(&stream.connection.device);
+                match waiter {
+                    Some(tx) => {
+                        if let Err(stream) = tx.send(stream) {
+                            let _ = stream.close();
+                        }
+                    }
+                    None => {
+                        warn!("Unexpected SPP connection from {}, closing", stream.connection.device);
+                        let _ = stream.close();
+                    }
+                }
+            }
+        });
+
+        *registered = true;
+        Ok(())
+    }
+
+    async fn open<R: Runtime>(
+        &self,
+        app: AppHandle<R>,
+        conn: &Connection,
+        profiles: &CustomProfiles,
+        address: &str,
+        options: SppOptions,
+        output: Option<mpsc::Sender<Vec<u8>>>,
+    ) -> Result<(String, mpsc::Sender<Vec<u8>>, Framing)> {
+        let framing = Framing::from_options(&options)?;
+        self.ensure_registered(&app, conn, profiles).await?;
+
+        let device = resolve_device_path(conn, address, None).await?;
+        info!("Opening SPP connection to {} ({})", address, device);
+        let stream = open_stream(conn, &self.waiters, &device).await?;
+
+        let id = format!("spp{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let (outgoing_tx, outgoing_rx) = mpsc::channel(WRITE_QUEUE);
+        let (paused_tx, paused_rx) = watch::channel(false);
+        let (closed_tx, closed_rx) = watch::channel(false);
+        let connected = Arc::new(AtomicBool::new(true));
+
+        self.sessions.lock().unwrap().insert(id.clone(), SppSession {
+            address: address.to_uppercase(),
+            device: device.clone(),
+            framing: framing.clone(),
+            outgoing: outgoing_tx.clone(),
+            connected: connected.clone(),
+            paused: paused_tx,
+            closed: closed_tx,
+        });
+
+        let task = SessionTask {
+            id: id.clone(),
+            device: device.clone(),
+            conn: conn.clone(),
+            waiters: self.waiters.clone(),
+            sessions: self.sessions.clone(),
+            framing: framing.clone(),
+            output: match output {
+                Some(tx) => Output::Channel(tx),
+                None => Output::Events(app.clone()),
+            },
+            events: app.clone(),
+            reconnect: options.reconnect.unwrap_or(false),
+            max_attempts: options.max_reconnect_attempts.unwrap_or(DEFAULT_RECONNECT_ATTEMPTS),
+            connected,
+        };
+        tauri::async_runtime::spawn(task.run(stream, outgoing_rx, paused_rx, closed_rx));
+
+        emit(&app, "spp-connected", serde_json::json!({ "id": id, "device": device }));
+        Ok((id, outgoing_tx, framing))
+    }
+
+    /// Opens an SPP connection whose data is surfaced as `spp-data` events.
+    pub async fn connect<R: Runtime>(
+        &self,
+        app: AppHandle<R>,
+        conn: &Connection,
+        profiles: &CustomProfiles,
+        address: &str,
+        options: SppOptions,
+    ) -> Result<String> {
+        let (id, _, _) = self.open(app, conn, profiles, address, options, None).await?;
+        Ok(id)
+    }
+
+    /// Opens an SPP connection for Rust code; messages arrive through the returned [`SppStream`].
+    pub async fn connect_stream<R: Runtime>(
+        &self,
+        app: AppHandle<R>,
+        conn: &Connection,
+        profiles: &CustomProfiles,
+        address: &str,
+        options: SppOptions,
+    ) -> Result<SppStream> {
+        let (incoming_tx, incoming_rx) = mpsc::channel(READ_QUEUE);
+        let (id, outgoing, framing) = self
+            .open(app, conn, profiles, address, options, Some(incoming_tx))
+            .await?;
+        Ok(SppStream {
+            id,
+            incoming: incoming_rx,
+            outgoing,
+            framing,
+        })
+    }
+
+    /// Queues one message (a line or frame in those modes); waits while the queue is full.
+    pub async fn write(&self, id: &str, data: &[u8]) -> Result<()> {
+        let (outgoing, bytes) = {
+            let sessions = self.sessions.lock().unwrap();
+            let session = sessions
+                .get(id)
+                .ok_or_else(|| Error::NotFound(format!("SPP connection not found: {}", id)))?;
+            (session.outgoing.clone(), session.framing.encode(data)?)
+        };
+        outgoing
+            .send(bytes)
+            .await
+            .map_err(|_| Error::NotFound(format!("SPP connection closed: {}", id)))
+    }
+
+    pub fn set_paused(&self, id: &str, paused: bool) -> Result<()> {
+        let sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get(id)
+            .ok_or_else(|| Error::NotFound(format!("SPP connection not found: {}", id)))?;
+        session.paused.send_replace(paused);
+        Ok(())
+    }
+
+    pub fn close(&self, id: &str) -> Result<()> {
+        let session = self
+            .sessions
+            .lock()
+            .unwrap()
+            .remove(id)
+            .ok_or_else(|| Error::NotFound(format!("SPP connection not found: {}", id)))?;
+        if session.closed.send(true).is_err() {
+            error!("SPP connection {} task already gone", id);
+        }
+        Ok(())
+    }
+
+    pub fn list(&self) -> Vec<SppConnectionInfo> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, session)| SppConnectionInfo {
+                id: id.clone(),
+                address: session.address.clone(),
+                device: session.device.clone(),
+                mode: session.framing.name().to_string(),
+                connected: session.connected.load(Ordering::Relaxed),
+                paused: *session.paused.borrow(),
+            })
+            .collect()
+    }
+}
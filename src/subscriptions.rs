@@ -0,0 +1,164 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{ipc::Channel, Manager, Runtime, Window, WindowEvent};
+
+use crate::desktop::BluetoothManager;
+use crate::device_lookup::{address_from_path, device_id};
+use crate::models::{BluetoothChange, SubscriptionFilter, SubscriptionInfo};
+use crate::object_cache::ObjectCache;
+use crate::{Error, Result};
+
+struct Subscription {
+    window: String,
+    filter: SubscriptionFilter,
+    channel: Channel<BluetoothChange>,
+}
+
+/// Scoped `bluetooth-change` delivery: each subscription gets the events matching its
+/// filter through its own Channel, so a window doesn't have to listen to (and wake up
+/// for) the global event. Subscriptions go away with their window.
+#[derive(Default)]
+pub struct Subscriptions {
+    subscriptions: Mutex<HashMap<u64, Subscription>>,
+    /// Windows whose close already removes their subscriptions.
+    watched: Mutex<HashSet<String>>,
+    next_id: AtomicU64,
+}
+
+/// Object path and address an event is about, whatever its shape.
+fn event_target(data: &serde_json::Value) -> (Option<String>, Option<String>) {
+    let path = ["path", "device"]
+        .iter()
+        .find_map(|key| data.get(*key).and_then(|v| v.as_str()))
+        .or_else(|| data.pointer("/info/path").and_then(|v| v.as_str()))
+        .filter(|p| p.starts_with('/'))
+        .map(|p| p.to_string());
+    let address = data
+        .get("address")
+        .and_then(|v| v.as_str())
+        .map(device_id)
+        .or_else(|| path.as_deref().and_then(address_from_path));
+    (path, address)
+}
+
+/// `paired` of the device snapshot an event carries, if any.
+fn event_paired(data: &serde_json::Value) -> Option<bool> {
+    ["/paired", "/device/paired", "/info/paired"]
+        .iter()
+        .find_map(|pointer| data.pointer(pointer).and_then(|v| v.as_bool()))
+}
+
+fn matches(filter: &SubscriptionFilter, change: &BluetoothChange, objects: &ObjectCache) -> bool {
+    if !filter.events.is_empty() && !filter.events.contains(&change.change_type) {
+        return false;
+    }
+    let (path, address) = event_target(&change.data);
+
+    if !filter.adapters.is_empty() {
+        let adapter = change
+            .data
+            .get("adapter")
+            .and_then(|v| v.as_str())
+            .map(|a| a.to_string())
+            .or_else(|| path.clone());
+        let on_adapter = adapter
+            .map(|p| filter.adapters.iter().any(|a| p == *a || p.starts_with(&format!("{}/", a))))
+            .unwrap_or(false);
+        if !on_adapter {
+            return false;
+        }
+    }
+
+    if !filter.devices.is_empty() {
+        let wanted = filter.devices.iter().any(|d| {
+            Some(d) == path.as_ref() || address.as_deref().map(|a| a.eq_ignore_ascii_case(d)).unwrap_or(false)
+        });
+        if !wanted {
+            return false;
+        }
+    }
+
+    if filter.paired_only && address.is_some() {
+        // The cache may not know the device (yet, or any more, as with `device-removed`);
+        // the payload's own snapshot answers then
+        let paired = path
+            .as_deref()
+            .and_then(|p| objects.device(p))
+            .map(|d| d.paired)
+            .or_else(|| event_paired(&change.data))
+            .unwrap_or(false);
+        if !paired {
+            return false;
+        }
+    }
+    true
+}
+
+impl Subscriptions {
+    /// Sends `change` to the subscriptions whose filter it matches; called from
+    /// `events::emit_change`.
+    pub(crate) fn dispatch(&self, change: &BluetoothChange, objects: &ObjectCache) {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        // A failed send means the webview is gone
+        subscriptions.retain(|_, sub| {
+            !matches(&sub.filter, change, objects) || sub.channel.send(change.clone()).is_ok()
+        });
+    }
+
+    pub fn subscribe<R: Runtime>(
+        &self,
+        window: &Window<R>,
+        filter: SubscriptionFilter,
+        channel: Channel<BluetoothChange>,
+    ) -> u64 {
+        let label = window.label().to_string();
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.subscriptions.lock().unwrap().insert(id, Subscription {
+            window: label.clone(),
+            filter,
+            channel,
+        });
+
+        if self.watched.lock().unwrap().insert(label.clone()) {
+            let app = window.app_handle().clone();
+            window.on_window_event(move |event| {
+                if matches!(event, WindowEvent::Destroyed) {
+                    app.state::<BluetoothManager>().subscriptions.remove_window(&label);
+                }
+            });
+        }
+        id
+    }
+
+    /// Ends subscription `id` if `window` owns it; other windows' ids are not found.
+    pub fn unsubscribe(&self, window: &str, id: u64) -> Result<()> {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        if subscriptions.get(&id).map(|sub| sub.window != window).unwrap_or(true) {
+            return Err(Error::NotFound(format!("Subscription not found: {}", id)));
+        }
+        subscriptions.remove(&id);
+        Ok(())
+    }
+
+    fn remove_window(&self, label: &str) {
+        self.subscriptions.lock().unwrap().retain(|_, sub| sub.window != label);
+        self.watched.lock().unwrap().remove(label);
+    }
+
+    pub fn list(&self) -> Vec<SubscriptionInfo> {
+        let mut list: Vec<SubscriptionInfo> = self
+            .subscriptions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, sub)| SubscriptionInfo {
+                id: *id,
+                window: sub.window.clone(),
+                filter: sub.filter.clone(),
+            })
+            .collect();
+        list.sort_by_key(|s| s.id);
+        list
+    }
+}
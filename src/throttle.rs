@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::desktop::BluetoothManager;
+use crate::device_lookup::address_from_path;
+use crate::events::{emit_change, BluetoothEvent};
+use crate::models::{ThrottleConfig};
+
+const TICK_INTERVAL: Duration = Duration::from_millis(50);
+/// Per-device state is dropped once nothing was emitted for this long.
+const IDLE_EXPIRY: Duration = Duration::from_secs(120);
+
+/// RSSI and ManufacturerData change on every advertisement; they are opt-in.
+fn allows(config: &ThrottleConfig, key: &str) -> bool {
+    match key {
+        "RSSI" => config.rssi,
+        "ManufacturerData" => config.manufacturer_data,
+        _ => true,
+    }
+}
+
+fn interval_for(config: &ThrottleConfig, key: &str) -> Duration {
+    if config.immediate.iter().any(|k| k == key) {
+        return Duration::ZERO;
+    }
+    Duration::from_millis(config.intervals.get(key).copied().unwrap_or(config.default_interval_ms))
+}
+
+#[derive(Default)]
+struct DeviceState {
+    last_emit: HashMap<String, Instant>,
+    pending: HashMap<String, (serde_json::Value, Instant)>,
+    window_end: Option<Instant>,
+}
+
+impl DeviceState {
+    fn take_due(&mut self, now: Instant, ready: &mut serde_json::Map<String, serde_json::Value>) {
+        let due: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, (_, at))| *at <= now)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in due {
+            if let Some((value, _)) = self.pending.remove(&key) {
+                ready.insert(key, value);
+            }
+        }
+        for key in ready.keys() {
+            self.last_emit.insert(key.clone(), now);
+        }
+        if self.pending.is_empty() {
+            self.window_end = None;
+        }
+    }
+}
+
+/// Rate limiting of `device-property-changed` events, per device and per property.
+///
+/// A property is delivered at most once per its interval; the latest value of a held
+/// property goes out when the interval ends, so the UI never keeps a stale value. With a
+/// coalescing window, non-immediate changes of a device are batched into one event.
+#[derive(Default)]
+pub struct EventThrottle {
+    config: Mutex<ThrottleConfig>,
+    devices: Mutex<HashMap<String, DeviceState>>,
+}
+
+fn emit_changes<R: Runtime>(
+    app: &AppHandle<R>,
+    device_path: &str,
+    changed: serde_json::Map<String, serde_json::Value>,
+    invalidated: Vec<String>,
+) {
+    let manager = app.state::<BluetoothManager>();
+    let mut device = None;
+    if manager.throttle.config().snapshot {
+        // Taken when the event goes out, so held values are already merged
+        device = manager.objects.device(device_path);
+        if let Some(device) = device.as_mut() {
+            manager.device_history.annotate(device);
+        }
+    }
+    emit_change(app, BluetoothEvent::DevicePropertyChanged {
+        path: device_path.to_string(),
+        address: address_from_path(device_path),
+        changed,
+        invalidated,
+        device,
+    });
+}
+
+impl EventThrottle {
+    pub fn new(config: ThrottleConfig) -> Self {
+        Self {
+            config: Mutex::new(config),
+            devices: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> ThrottleConfig {
+        self.config.lock().unwrap().clone()
+    }
+
+    pub fn set_config(&self, config: ThrottleConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// Takes the changed and invalidated `Device1` properties of one signal and emits what
+    /// is due now. Invalidated keys are never held back.
+    pub fn push<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        device_path: &str,
+        changed: impl IntoIterator<Item = (String, serde_json::Value)>,
+        invalidated: Vec<String>,
+    ) {
+        let config = self.config();
+        let now = Instant::now();
+        let coalesce = Duration::from_millis(config.coalesce_ms);
+        let mut ready = serde_json::Map::new();
+        let invalidated: Vec<String> = invalidated.into_iter().filter(|key| allows(&config, key)).collect();
+
+        {
+            let mut devices = self.devices.lock().unwrap();
+            let state = devices.entry(device_path.to_string()).or_default();
+            for key in &invalidated {
+                state.pending.remove(key);
+            }
+            for (key, value) in changed {
+                if !allows(&config, &key) {
+                    continue;
+                }
+                let interval = interval_for(&config, &key);
+                let due = if interval.is_zero() {
+                    now
+                } else {
+                    let earliest = state.last_emit.get(&key).map(|t| *t + interval).unwrap_or(now);
+                    let window_end = if coalesce.is_zero() {
+                        now
+                    } else {
+                        *state.window_end.get_or_insert(now + coalesce)
+                    };
+                    earliest.max(window_end)
+                };
+                if due <= now {
+                    state.pending.remove(&key);
+                    ready.insert(key, value);
+                } else {
+                    state.pending.insert(key, (value, due));
+                }
+            }
+            if !ready.is_empty() || !invalidated.is_empty() {
+                state.take_due(now, &mut ready);
+            }
+        }
+
+        if !ready.is_empty() || !invalidated.is_empty() {
+            emit_changes(app, device_path, ready, invalidated);
+        }
+    }
+
+    fn flush<R: Runtime>(&self, app: &AppHandle<R>) {
+        let now = Instant::now();
+        let mut batches = Vec::new();
+        {
+            let mut devices = self.devices.lock().unwrap();
+            for (path, state) in devices.iter_mut() {
+                let mut ready = serde_json::Map::new();
+                state.take_due(now, &mut ready);
+                if !ready.is_empty() {
+                    batches.push((path.clone(), ready));
+                }
+            }
+            devices.retain(|_, state| {
+                !state.pending.is_empty()
+                    || state.last_emit.values().any(|t| now.duration_since(*t) < IDLE_EXPIRY)
+            });
+        }
+        for (path, changed) in batches {
+            emit_changes(app, &path, changed, Vec::new());
+        }
+    }
+
+    /// Emits held properties once their interval or coalescing window ends.
+    pub fn spawn_flusher<R: Runtime>(app: AppHandle<R>) {
+        tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::time::sleep(TICK_INTERVAL).await;
+                app.state::<BluetoothManager>().throttle.flush(&app);
+            }
+        });
+    }
+}
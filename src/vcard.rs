@@ -0,0 +1,339 @@
+use crate::models::{VCardContact, VCardField};
+
+/// A single unfolded vCard line split into its parts.
+struct Property {
+    name: String,
+    types: Vec<String>,
+    encoding: Option<String>,
+    charset: Option<String>,
+    value: String,
+}
+
+fn is_quoted_printable(line: &str) -> bool {
+    line.split(':')
+        .next()
+        .map(|head| head.to_ascii_uppercase().contains("QUOTED-PRINTABLE"))
+        .unwrap_or(false)
+}
+
+/// Joins folded lines: RFC 2425 whitespace continuations and vCard 2.1
+/// quoted-printable soft line breaks (a trailing `=`).
+fn unfold(input: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in input.split('\n') {
+        let line = raw.strip_suffix('\r').unwrap_or(raw);
+        if let Some(last) = lines.last_mut() {
+            if is_quoted_printable(last) && last.ends_with('=') {
+                last.pop();
+                last.push_str(line.trim_start());
+                continue;
+            }
+            if line.starts_with(' ') || line.starts_with('\t') {
+                last.push_str(&line[1..]);
+                continue;
+            }
+        }
+        if !line.trim().is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+    lines
+}
+
+fn parse_property(line: &str) -> Option<Property> {
+    let (head, value) = line.split_once(':')?;
+    let mut parts = head.split(';');
+
+    // Drop the optional group prefix ("item1.TEL")
+    let name = parts.next()?;
+    let name = name.rsplit('.').next().unwrap_or(name).to_ascii_uppercase();
+
+    let mut property = Property {
+        name,
+        types: Vec::new(),
+        encoding: None,
+        charset: None,
+        value: value.to_string(),
+    };
+
+    for param in parts {
+        match param.split_once('=') {
+            Some((key, val)) => match key.trim().to_ascii_uppercase().as_str() {
+                "TYPE" => property.types.extend(
+                    val.split(',')
+                        .map(|t| t.trim_matches('"').to_ascii_lowercase())
+                        .filter(|t| !t.is_empty()),
+                ),
+                "ENCODING" => property.encoding = Some(val.to_ascii_uppercase()),
+                "CHARSET" => property.charset = Some(val.to_ascii_uppercase()),
+                _ => {}
+            },
+            // vCard 2.1 allows bare parameters: "TEL;CELL;VOICE:" or "NOTE;QUOTED-PRINTABLE:"
+            None => {
+                let upper = param.trim().to_ascii_uppercase();
+                match upper.as_str() {
+                    "QUOTED-PRINTABLE" | "BASE64" | "B" | "8BIT" | "7BIT" => {
+                        property.encoding = Some(upper)
+                    }
+                    "" => {}
+                    _ => property.types.push(upper.to_ascii_lowercase()),
+                }
+            }
+        }
+    }
+
+    Some(property)
+}
+
+fn decode_quoted_printable(value: &str) -> Vec<u8> {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'=' {
+            let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    out
+}
+
+/// Returns the decoded text value, or `None` for binary (base64) properties such as PHOTO.
+fn decoded_value(property: &Property) -> Option<String> {
+    let bytes = match property.encoding.as_deref() {
+        Some("BASE64") | Some("B") => return None,
+        Some("QUOTED-PRINTABLE") => decode_quoted_printable(&property.value),
+        _ => return Some(property.value.clone()),
+    };
+    match property.charset.as_deref() {
+        Some("ISO-8859-1") | Some("LATIN1") | Some("WINDOWS-1252") => {
+            Some(bytes.iter().map(|b| *b as char).collect())
+        }
+        _ => Some(String::from_utf8_lossy(&bytes).to_string()),
+    }
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Splits a structured value (N, ADR, ORG) on unescaped `;` and unescapes each component.
+fn split_structured(value: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut escaped = false;
+    for c in value.chars() {
+        if escaped {
+            current.push('\\');
+            current.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == ';' {
+            parts.push(unescape(&current));
+            current.clear();
+        } else {
+            current.push(c);
+        }
+    }
+    parts.push(unescape(&current));
+    parts
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
+
+fn join_components(parts: &[String], separator: &str) -> String {
+    parts
+        .iter()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Parses every `BEGIN:VCARD` ... `END:VCARD` block (vCard 2.1 or 3.0) in `input`.
+pub fn parse_vcards(input: &str) -> Vec<VCardContact> {
+    let mut contacts = Vec::new();
+    let mut current: Option<VCardContact> = None;
+
+    for line in unfold(input) {
+        let Some(property) = parse_property(&line) else {
+            continue;
+        };
+
+        match property.name.as_str() {
+            "BEGIN" if property.value.trim().eq_ignore_ascii_case("VCARD") => {
+                current = Some(VCardContact::default());
+                continue;
+            }
+            "END" if property.value.trim().eq_ignore_ascii_case("VCARD") => {
+                if let Some(mut contact) = current.take() {
+                    if contact.formatted_name.is_none() {
+                        let parts: Vec<String> = [contact.given_name.clone(), contact.family_name.clone()]
+                            .into_iter()
+                            .flatten()
+                            .collect();
+                        contact.formatted_name = non_empty(parts.join(" "));
+                    }
+                    contacts.push(contact);
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        let Some(contact) = current.as_mut() else {
+            continue;
+        };
+        let Some(value) = decoded_value(&property) else {
+            continue;
+        };
+
+        match property.name.as_str() {
+            "VERSION" => contact.version = non_empty(value),
+            "UID" => contact.uid = non_empty(unescape(&value)),
+            "FN" => contact.formatted_name = non_empty(unescape(&value)),
+            "N" => {
+                let parts = split_structured(&value);
+                contact.family_name = parts.first().cloned().and_then(non_empty);
+                contact.given_name = parts.get(1).cloned().and_then(non_empty);
+            }
+            "TEL" => {
+                if let Some(number) = non_empty(unescape(&value)) {
+                    contact.phones.push(VCardField {
+                        value: number,
+                        types: property.types,
+                    });
+                }
+            }
+            "EMAIL" => {
+                if let Some(email) = non_empty(unescape(&value)) {
+                    contact.emails.push(VCardField {
+                        value: email,
+                        types: property.types,
+                    });
+                }
+            }
+            "ADR" => {
+                let address = join_components(&split_structured(&value), ", ");
+                if let Some(address) = non_empty(address) {
+                    contact.addresses.push(VCardField {
+                        value: address,
+                        types: property.types,
+                    });
+                }
+            }
+            "ORG" => contact.organization = non_empty(join_components(&split_structured(&value), ", ")),
+            "TITLE" => contact.title = non_empty(unescape(&value)),
+            "BDAY" => contact.birthday = non_empty(value),
+            "NOTE" => contact.note = non_empty(unescape(&value)),
+            "X-IRMC-CALL-DATETIME" => {
+                contact.call_type = property.types.into_iter().next();
+                contact.call_datetime = non_empty(value);
+            }
+            _ => {}
+        }
+    }
+
+    contacts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_vcard_21_quoted_printable_with_soft_breaks() {
+        let input = "BEGIN:VCARD\r\n\
+                     VERSION:2.1\r\n\
+                     N;CHARSET=UTF-8;ENCODING=QUOTED-PRINTABLE:P=C3=A9rez;Jos=C3=A9;;;\r\n\
+                     FN;CHARSET=UTF-8;QUOTED-PRINTABLE:Jos=C3=A9 =\r\n\
+                     P=C3=A9rez\r\n\
+                     NOTE;ENCODING=QUOTED-PRINTABLE;CHARSET=ISO-8859-1:Caf=E9 con le=\r\n\
+                     che\r\n\
+                     TEL;CELL;VOICE:+34600111222\r\n\
+                     END:VCARD\r\n";
+        let contacts = parse_vcards(input);
+        assert_eq!(contacts.len(), 1);
+        let contact = &contacts[0];
+        assert_eq!(contact.version.as_deref(), Some("2.1"));
+        assert_eq!(contact.formatted_name.as_deref(), Some("José Pérez"));
+        assert_eq!(contact.family_name.as_deref(), Some("Pérez"));
+        assert_eq!(contact.given_name.as_deref(), Some("José"));
+        assert_eq!(contact.note.as_deref(), Some("Café con leche"));
+        assert_eq!(contact.phones.len(), 1);
+        assert_eq!(contact.phones[0].value, "+34600111222");
+        assert_eq!(contact.phones[0].types, vec!["cell", "voice"]);
+    }
+
+    #[test]
+    fn unfolds_vcard_30_lines() {
+        let input = "BEGIN:VCARD\r\n\
+                     VERSION:3.0\r\n\
+                     FN:Jane\r\n  Doe\r\n\
+                     EMAIL;TYPE=INTERNET,HOME:jane@exa\r\n mple.com\r\n\
+                     NOTE:Comma\\, semicolon\\; and\\nnewline\r\n\
+                     PHOTO;ENCODING=b;TYPE=JPEG:AAAA\r\n BBBB\r\n\
+                     END:VCARD\r\n";
+        let contacts = parse_vcards(input);
+        assert_eq!(contacts.len(), 1);
+        let contact = &contacts[0];
+        assert_eq!(contact.formatted_name.as_deref(), Some("Jane Doe"));
+        assert_eq!(contact.emails.len(), 1);
+        assert_eq!(contact.emails[0].value, "jane@example.com");
+        assert_eq!(contact.emails[0].types, vec!["internet", "home"]);
+        assert_eq!(contact.note.as_deref(), Some("Comma, semicolon; and\nnewline"));
+    }
+
+    #[test]
+    fn reads_grouped_properties() {
+        let input = "BEGIN:VCARD\n\
+                     VERSION:3.0\n\
+                     N:Doe;John;;;\n\
+                     item1.TEL;type=CELL:+1 555 0100\n\
+                     item1.X-ABLabel:mobile\n\
+                     item2.ADR;type=HOME:;;1 Main St;Springfield;;12345;USA\n\
+                     END:VCARD\n\
+                     BEGIN:VCARD\n\
+                     VERSION:3.0\n\
+                     FN:Second\n\
+                     END:VCARD\n";
+        let contacts = parse_vcards(input);
+        assert_eq!(contacts.len(), 2);
+        let contact = &contacts[0];
+        // No FN: built from N
+        assert_eq!(contact.formatted_name.as_deref(), Some("John Doe"));
+        assert_eq!(contact.phones.len(), 1);
+        assert_eq!(contact.phones[0].value, "+1 555 0100");
+        assert_eq!(contact.phones[0].types, vec!["cell"]);
+        assert_eq!(contact.addresses.len(), 1);
+        assert_eq!(contact.addresses[0].value, "1 Main St, Springfield, 12345, USA");
+        assert_eq!(contacts[1].formatted_name.as_deref(), Some("Second"));
+    }
+}